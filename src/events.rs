@@ -0,0 +1,193 @@
+use near_sdk::{env, serde::Serialize, json_types::U128, AccountId};
+
+use crate::models::{TaskId, TaskState, HabitId, RewardId, TimeSlotId, Role,
+    PointsEntryKind, PointsSource, PenaltyPolicy};
+
+// NEP-297 envelope; bump the version whenever an event's payload changes shape
+pub const EVENT_STANDARD: &str = "time_tactician";
+pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+const EVENT_LOG_PREFIX: &str = "EVENT_JSON:";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a ContractEvent,
+}
+
+// === Events ===
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum ContractEvent {
+    TaskCreated {
+        task_id: TaskId,
+        owner_id: AccountId,
+        parent_task_id: Option<TaskId>,
+        deadline: u64,
+        reward_points: u32,
+    },
+    TaskUpdated {
+        task_id: TaskId,
+        owner_id: AccountId,
+    },
+    TaskStateChanged {
+        task_id: TaskId,
+        owner_id: AccountId,
+        old_state: TaskState,
+        new_state: TaskState,
+    },
+    TaskCompleted {
+        task_id: TaskId,
+        owner_id: AccountId,
+        reward_points: u32,
+    },
+    TaskDeleted {
+        task_id: TaskId,
+        owner_id: AccountId,
+    },
+    OverdueSwept {
+        owner_id: AccountId,
+        keeper_id: AccountId,
+        task_count: u64,
+        keeper_fee: U128,
+    },
+    TaskDependencyAdded {
+        task_id: TaskId,
+        blocker_id: TaskId,
+        owner_id: AccountId,
+    },
+    TaskDependencyRemoved {
+        task_id: TaskId,
+        blocker_id: TaskId,
+        owner_id: AccountId,
+    },
+    HabitCreated {
+        habit_id: HabitId,
+        task_id: TaskId,
+        owner_id: AccountId,
+    },
+    HabitUpdated {
+        habit_id: HabitId,
+        owner_id: AccountId,
+    },
+    HabitPaused {
+        habit_id: HabitId,
+        owner_id: AccountId,
+    },
+    HabitResumed {
+        habit_id: HabitId,
+        owner_id: AccountId,
+    },
+    HabitDeleted {
+        habit_id: HabitId,
+        task_id: TaskId,
+        owner_id: AccountId,
+    },
+    HabitStreakUpdated {
+        habit_id: HabitId,
+        task_id: TaskId,
+        owner_id: AccountId,
+        streak: u32,
+        next_deadline: u64,
+    },
+    HabitEnded {
+        habit_id: HabitId,
+        task_id: TaskId,
+        owner_id: AccountId,
+        streak: u32,
+    },
+    StreakFreezesPurchased {
+        account_id: AccountId,
+        quantity: u32,
+        cost: u32,
+        balance: u32,
+    },
+    StreakFreezesUsed {
+        habit_id: HabitId,
+        owner_id: AccountId,
+        occurrence_days: Vec<u64>,
+        remaining: u32,
+    },
+    PointsChanged {
+        account_id: AccountId,
+        old_balance: u32,
+        new_balance: u32,
+        kind: PointsEntryKind,
+        source: PointsSource,
+        amount: u32,
+    },
+    RewardCreated {
+        reward_id: RewardId,
+        owner_id: AccountId,
+        cost: u32,
+    },
+    RewardUpdated {
+        reward_id: RewardId,
+        owner_id: AccountId,
+        cost: u32,
+    },
+    RewardDeleted {
+        reward_id: RewardId,
+        owner_id: AccountId,
+    },
+    RewardRedeemed {
+        reward_id: RewardId,
+        owner_id: AccountId,
+        cost: u32,
+    },
+    TimeSlotCreated {
+        slot_id: TimeSlotId,
+        owner_id: AccountId,
+    },
+    TimeSlotUpdated {
+        slot_id: TimeSlotId,
+        owner_id: AccountId,
+    },
+    TimeSlotDeleted {
+        slot_id: TimeSlotId,
+        owner_id: AccountId,
+    },
+    SettingsUpdated {
+        account_id: AccountId,
+        utc_offset_minutes: i32,
+    },
+    PenaltyPolicyUpdated {
+        account_id: AccountId,
+        policy: Option<PenaltyPolicy>,
+    },
+    RoleGranted {
+        account_id: AccountId,
+        role: Role,
+        granted_by: AccountId,
+    },
+    RoleRevoked {
+        account_id: AccountId,
+        revoked_by: AccountId,
+    },
+    ContractPaused {
+        account_id: AccountId,
+    },
+    ContractUnpaused {
+        account_id: AccountId,
+    },
+    ConfigUpdated {
+        account_id: AccountId,
+    },
+}
+
+// === Emission ===
+impl ContractEvent {
+    pub fn emit(&self) {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            event: self,
+        };
+        let json = near_sdk::serde_json::to_string(&log)
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize event"));
+        env::log_str(&format!("{}{}", EVENT_LOG_PREFIX, json));
+    }
+}
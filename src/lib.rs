@@ -1,25 +1,52 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
+use std::collections::{HashMap, HashSet, VecDeque};
 use near_sdk::{
-    near, env, PanicOnDefault, AccountId,
-    collections::{LookupMap, UnorderedMap, UnorderedSet},
+    near, env, PanicOnDefault, AccountId, NearToken, Promise,
+    json_types::U128,
+    collections::{LookupMap, UnorderedMap, UnorderedSet, TreeMap, Vector},
 };
 
 pub mod models;
+pub mod events;
+mod migration;
+use crate::migration::{VersionedState, write_state_version, MIGRATE_GAS};
+use crate::events::ContractEvent;
 use crate::models::{
-    Task, TaskId, Priority, TaskState, TaskTimeSlot,
+    Task, TaskId, Priority, TaskState, TaskTimeSlot, TaskTreeNode, OverdueSweep,
+    TaskQuery, TaskPage, TaskSortKey,
     TaskError, TaskValidationError, TaskStateError,
 
-    Habit, HabitId,
+    Habit, HabitId, HabitStats, HabitError, HabitStateError,
+    HabitHistoryEntry, HabitHistoryKind, HabitHistoryPage,
 
     Reward, RewardId, RewardState, 
     RewardError, RewardValidationError, RewardStateError,
 
-    TimeSlot, TimeSlotId, SlotType, RecurrencePattern,
-    Frequency, DayOfWeek, TimeSlotError, TimeSlotValidationError,
+    TimeSlot, TimeSlotId, SlotType, SlotOccurrence, RecurrencePattern,
+    TimeSlotError, TimeSlotValidationError,
 
-    StorageError, OwnershipError, Ownable,
+    AccountSettings, PenaltyPolicy, SettingsValidationError,
+
+    StorageAccount, StorageBalance, StorageBalanceBounds, StorageReport, EntityStorage,
+
+    Role, ContractConfig, ConfigValidationError,
+    storage::{nested_prefix, state_index_prefix, habit_history_prefix,
+        lookup_entry_bytes, vector_entry_bytes, borsh_len},
+
+    PointsEntry, PointsEntryKind, PointsSource, PointsHistoryPage, PointsError,
+
+    SchedulePlan, ScheduleConflict, ConflictSource,
+    schedule::{self, Window},
+    dependency,
+    task::SubtaskError,
+    config::{prefix, recurrence::{MAX_STATS_DAYS, MAX_STATS_PERIODS}},
+    calendar::day_index,
+
+    StorageError, OwnershipError, Ownable, Storable,
+
+    VersionedTask, VersionedHabit, VersionedReward, VersionedTimeSlot,
 };
 
 // === Core Enums ===
@@ -67,15 +94,26 @@ pub enum ContractError {
     AccessError(OwnershipError), 
     StateError(String, String, String, String), // entity, current_state, attempted_action, message
     NotFound(String, String), // entity, id
-    Operation(String) // error message
+    AlreadyExists(String, String), // entity, id
+    Operation(String), // error message
+    ScheduleConflict(Vec<ScheduleConflict>),
+    Unauthorized(String, String), // account, required role
+    Paused,
+    PointsError(PointsError)
 }
 
 // === Type aliases for response types ===
 pub type TaskResponse = Response<Task, ContractError>;
 pub type TaskListResponse = Response<Vec<Task>, ContractError>;
 pub type TaskActionResponse = Response<TaskId, ContractError>;
+pub type TaskTreeResponse = Response<TaskTreeNode, ContractError>;
+pub type TaskPageResponse = Response<TaskPage, ContractError>;
+pub type OverdueSweepResponse = Response<OverdueSweep, ContractError>;
 
 pub type HabitListResponse = Response<Vec<Habit>, ContractError>;
+pub type HabitActionResponse = Response<HabitId, ContractError>;
+pub type HabitStatsResponse = Response<HabitStats, ContractError>;
+pub type HabitHistoryResponse = Response<HabitHistoryPage, ContractError>;
 
 pub type RewardResponse = Response<Reward, ContractError>;
 pub type RewardActionResponse = Response<RewardId, ContractError>;
@@ -84,8 +122,20 @@ pub type RewardListResponse = Response<Vec<Reward>, ContractError>;
 pub type TimeSlotResponse = Response<TimeSlot, ContractError>;
 pub type TimeSlotListResponse = Response<Vec<TimeSlot>, ContractError>;
 pub type TimeSlotActionResponse = Response<TimeSlotId, ContractError>;
+pub type SlotOccurrenceListResponse = Response<Vec<SlotOccurrence>, ContractError>;
 
 pub type PointsResponse = Response<u32, ContractError>;
+pub type PointsHistoryResponse = Response<PointsHistoryPage, ContractError>;
+
+pub type AccountSettingsResponse = Response<AccountSettings, ContractError>;
+pub type PenaltyPolicyResponse = Response<Option<PenaltyPolicy>, ContractError>;
+
+pub type SchedulePlanResponse = Response<SchedulePlan, ContractError>;
+
+pub type StorageReportResponse = Response<StorageReport, ContractError>;
+
+pub type ContractConfigResponse = Response<ContractConfig, ContractError>;
+pub type RoleListResponse = Response<Vec<(AccountId, Role)>, ContractError>;
 
 // === Error Conversion Implementations ===
 impl From<StorageError> for ContractError {
@@ -100,6 +150,12 @@ impl From<OwnershipError> for ContractError {
     }
 }
 
+impl From<PointsError> for ContractError {
+    fn from(err: PointsError) -> Self {
+        ContractError::PointsError(err)
+    }
+}
+
 // Task error conversions
 impl From<TaskError> for ContractError {
     fn from(err: TaskError) -> Self {
@@ -140,11 +196,43 @@ impl From<TaskStateError> for ContractError {
                 format!("{:?}", state),
                 format!("{:?}", action),
                 "Invalid action for current state".to_string()
+            ),
+            TaskStateError::Blocked { state, blocker_ids } => ContractError::StateError(
+                "Task".to_string(),
+                format!("{:?}", state),
+                "proceed while blocked".to_string(),
+                format!("Blocked by incomplete tasks: {}", blocker_ids.join(", "))
             )
         }
     }
 }
 
+// Habit error conversions
+impl From<HabitError> for ContractError {
+    fn from(err: HabitError) -> Self {
+        match err {
+            HabitError::Validation(err) => ContractError::ValidationError(
+                "Habit".to_string(),
+                err.to_string(),
+                None
+            ),
+            HabitError::Storage(err) => ContractError::StorageError(err),
+            HabitError::State(err) => {
+                let (state, action) = match err {
+                    HabitStateError::AlreadyPaused => ("Paused", "pause"),
+                    HabitStateError::NotPaused => ("Active", "resume"),
+                };
+                ContractError::StateError(
+                    "Habit".to_string(),
+                    state.to_string(),
+                    action.to_string(),
+                    err.to_string()
+                )
+            }
+        }
+    }
+}
+
 // Reward error conversions
 impl From<RewardError> for ContractError {
     fn from(err: RewardError) -> Self {
@@ -205,6 +293,27 @@ impl From<TimeSlotError> for ContractError {
     }
 }
 
+// Settings error conversions
+impl From<ConfigValidationError> for ContractError {
+    fn from(err: ConfigValidationError) -> Self {
+        ContractError::ValidationError(
+            "Config".to_string(),
+            err.to_string(),
+            None
+        )
+    }
+}
+
+impl From<SettingsValidationError> for ContractError {
+    fn from(err: SettingsValidationError) -> Self {
+        ContractError::ValidationError(
+            "Account".to_string(),
+            err.to_string(),
+            None
+        )
+    }
+}
+
 impl From<TimeSlotValidationError> for ContractError {
     fn from(err: TimeSlotValidationError) -> Self {
         ContractError::ValidationError(
@@ -233,7 +342,15 @@ impl std::fmt::Display for ContractError {
                     entity, message, current_state, attempted_action)
             },
             Self::NotFound(entity, id) => write!(f, "{} not found: {}", entity, id),
-            Self::Operation(err) => write!(f, "Operation error: {}", err)
+            Self::AlreadyExists(entity, id) => write!(f, "{} already exists: {}", entity, id),
+            Self::Operation(err) => write!(f, "Operation error: {}", err),
+            Self::ScheduleConflict(conflicts) => {
+                let conflicts: Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+                write!(f, "Schedule conflict with: {}", conflicts.join(", "))
+            },
+            Self::Unauthorized(account, role) => write!(f, "{} does not have the {} role", account, role),
+            Self::Paused => write!(f, "Contract is paused"),
+            Self::PointsError(err) => write!(f, "Points error: {}", err)
         }
     }
 }
@@ -242,35 +359,93 @@ impl std::fmt::Display for ContractError {
 #[near(contract_state)]
 #[derive(PanicOnDefault)]
 pub struct Contract {
-    tasks: UnorderedMap<TaskId, Task>,
+    tasks: UnorderedMap<TaskId, VersionedTask>,
     tasks_per_owner: LookupMap<AccountId, UnorderedSet<TaskId>>,
-    habits: UnorderedMap<HabitId, Habit>,
+    habits: UnorderedMap<HabitId, VersionedHabit>,
     habits_per_owner: LookupMap<AccountId, UnorderedSet<HabitId>>,
     task_completions: LookupMap<TaskId, Vec<u64>>,
     reward_points: LookupMap<AccountId, u32>,
-    rewards: UnorderedMap<RewardId, Reward>,
+    rewards: UnorderedMap<RewardId, VersionedReward>,
     rewards_per_owner: LookupMap<AccountId, UnorderedSet<RewardId>>,
-    time_slots: UnorderedMap<TimeSlotId, TimeSlot>,
+    time_slots: UnorderedMap<TimeSlotId, VersionedTimeSlot>,
     time_slots_per_owner: LookupMap<AccountId, UnorderedSet<TimeSlotId>>,
+    account_settings: LookupMap<AccountId, AccountSettings>,
+    task_blockers: LookupMap<TaskId, Vec<TaskId>>,
+    task_dependents: LookupMap<TaskId, Vec<TaskId>>,
+    // Ordered by deadline, so overdue tasks drop out of the open states' trees
+    tasks_by_state: LookupMap<(AccountId, TaskState), TreeMap<(u64, TaskId), ()>>,
+    habit_by_task: LookupMap<TaskId, HabitId>,
+    id_nonces: LookupMap<AccountId, u64>,
+    storage_accounts: LookupMap<AccountId, StorageAccount>,
+    roles: UnorderedMap<AccountId, Role>,
+    config: ContractConfig,
+    paused: bool,
+    // Append-only history of every reward_points change, per account
+    points_ledger: LookupMap<AccountId, Vector<PointsEntry>>,
+    // Accounts without a policy are never penalised
+    penalty_policies: LookupMap<AccountId, PenaltyPolicy>,
+    // Append-only streak history per habit, removed with the habit
+    habit_history: LookupMap<HabitId, Vector<HabitHistoryEntry>>,
+    // Unused streak freezes per account, bought with reward points
+    streak_freezes: LookupMap<AccountId, u32>,
 }  
 
 
 #[near]
 impl Contract {
     #[init]
-    pub fn new() -> Self {
-        Self {
-            tasks: UnorderedMap::new(b"t".to_vec()),
-            tasks_per_owner: LookupMap::new(b"to".to_vec()),
-            habits: UnorderedMap::new(b"h".to_vec()),
-            habits_per_owner: LookupMap::new(b"ho".to_vec()),
-            task_completions: LookupMap::new(b"tc".to_vec()),
-            reward_points: LookupMap::new(b"rp".to_vec()),
-            rewards: UnorderedMap::new(b"r".to_vec()),
-            rewards_per_owner: LookupMap::new(b"ro".to_vec()),
-            time_slots: UnorderedMap::new(b"ts".to_vec()),
-            time_slots_per_owner: LookupMap::new(b"tso".to_vec()),
+    pub fn new(admin_id: Option<AccountId>) -> Self {
+        write_state_version();
+        let mut contract = Self {
+            tasks: UnorderedMap::new(prefix::TASKS.as_bytes()),
+            tasks_per_owner: LookupMap::new(prefix::TASKS_PER_OWNER.as_bytes()),
+            habits: UnorderedMap::new(prefix::HABITS.as_bytes()),
+            habits_per_owner: LookupMap::new(prefix::HABITS_PER_OWNER.as_bytes()),
+            task_completions: LookupMap::new(prefix::TASK_COMPLETIONS.as_bytes()),
+            reward_points: LookupMap::new(prefix::REWARD_POINTS.as_bytes()),
+            rewards: UnorderedMap::new(prefix::REWARDS.as_bytes()),
+            rewards_per_owner: LookupMap::new(prefix::REWARDS_PER_OWNER.as_bytes()),
+            time_slots: UnorderedMap::new(prefix::TIME_SLOTS.as_bytes()),
+            time_slots_per_owner: LookupMap::new(prefix::TIME_SLOTS_PER_OWNER.as_bytes()),
+            account_settings: LookupMap::new(prefix::ACCOUNT_SETTINGS.as_bytes()),
+            task_blockers: LookupMap::new(prefix::TASK_BLOCKERS.as_bytes()),
+            task_dependents: LookupMap::new(prefix::TASK_DEPENDENTS.as_bytes()),
+            tasks_by_state: LookupMap::new(prefix::TASKS_BY_STATE.as_bytes()),
+            habit_by_task: LookupMap::new(prefix::HABIT_BY_TASK.as_bytes()),
+            id_nonces: LookupMap::new(prefix::ID_NONCES.as_bytes()),
+            storage_accounts: LookupMap::new(prefix::STORAGE_ACCOUNTS.as_bytes()),
+            roles: UnorderedMap::new(prefix::ROLES.as_bytes()),
+            config: ContractConfig::default(),
+            paused: false,
+            points_ledger: LookupMap::new(prefix::POINTS_LEDGER.as_bytes()),
+            penalty_policies: LookupMap::new(prefix::PENALTY_POLICIES.as_bytes()),
+            habit_history: LookupMap::new(prefix::HABIT_HISTORY.as_bytes()),
+            streak_freezes: LookupMap::new(prefix::STREAK_FREEZES.as_bytes()),
+        };
+
+        let admin_id = admin_id.unwrap_or_else(env::predecessor_account_id);
+        contract.roles.insert(&admin_id, &Role::Admin);
+        contract
+    }
+
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let contract = VersionedState::read().into_current();
+        write_state_version();
+        contract
+    }
+
+    // Takes the new wasm as raw call input, deploys it and migrates in the same batch
+    pub fn upgrade(&self) -> Promise {
+        if let Err(e) = self.require_role(Role::Admin) {
+            env::panic_str(&e.to_string());
         }
+
+        let code = env::input().unwrap_or_else(|| env::panic_str("Expected the contract code as input"));
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), Vec::new(), NearToken::from_yoctonear(0), MIGRATE_GAS)
     }
 
     fn add_to_owner_index(&mut self, owner_id: &AccountId, id: &str, index_type: IndexType) {
@@ -278,28 +453,28 @@ impl Contract {
             IndexType::Task => {
                 let mut task_set = self.tasks_per_owner
                     .get(owner_id)
-                    .unwrap_or_else(|| UnorderedSet::new(format!("to{}", owner_id).as_bytes()));
+                    .unwrap_or_else(|| UnorderedSet::new(nested_prefix(prefix::TASKS_PER_OWNER, owner_id)));
                 task_set.insert(&id.to_string());
                 self.tasks_per_owner.insert(owner_id, &task_set);
             },
             IndexType::Habit => {
                 let mut habit_set = self.habits_per_owner
                     .get(owner_id)
-                    .unwrap_or_else(|| UnorderedSet::new(format!("ho{}", owner_id).as_bytes()));
+                    .unwrap_or_else(|| UnorderedSet::new(nested_prefix(prefix::HABITS_PER_OWNER, owner_id)));
                 habit_set.insert(&id.to_string());
                 self.habits_per_owner.insert(owner_id, &habit_set);
             },
             IndexType::Reward => {
                 let mut reward_set = self.rewards_per_owner
                     .get(owner_id)
-                    .unwrap_or_else(|| UnorderedSet::new(format!("ro{}", owner_id).as_bytes()));
+                    .unwrap_or_else(|| UnorderedSet::new(nested_prefix(prefix::REWARDS_PER_OWNER, owner_id)));
                 reward_set.insert(&id.to_string());
                 self.rewards_per_owner.insert(owner_id, &reward_set);
             },
             IndexType::TimeSlot => {
                 let mut slot_set = self.time_slots_per_owner
                     .get(owner_id)
-                    .unwrap_or_else(|| UnorderedSet::new(format!("tso{}", owner_id).as_bytes()));
+                    .unwrap_or_else(|| UnorderedSet::new(nested_prefix(prefix::TIME_SLOTS_PER_OWNER, owner_id)));
                 slot_set.insert(&id.to_string());
                 self.time_slots_per_owner.insert(owner_id, &slot_set);
             },
@@ -351,231 +526,1030 @@ impl Contract {
         }
     }
 
-    fn add_reward_points(&mut self, account_id: AccountId, points: u32) -> PointsResponse {
-        if account_id.to_string().is_empty() {
-            return Response::Error(ContractError::ValidationError(
-                "Account".to_string(),
-                "Account ID cannot be empty".to_string(),
-                None
-            ));
+    pub fn get_points_history(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>
+    ) -> PointsHistoryResponse {
+        let ledger = match self.points_ledger.get(&account_id) {
+            Some(ledger) => ledger,
+            None => return Response::Success(PointsHistoryPage { entries: Vec::new(), total: 0 })
+        };
+
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(self.config.default_page_limit).min(self.config.max_page_limit);
+        let entries = (from_index..ledger.len().min(from_index.saturating_add(limit)))
+            .filter_map(|index| ledger.get(index))
+            .collect();
+
+        Response::Success(PointsHistoryPage { entries, total: ledger.len() })
+    }
+
+    fn credit_points(&mut self, account_id: &AccountId, amount: u32, source: PointsSource) -> PointsResponse {
+        self.change_points(account_id, PointsEntryKind::Credit, amount, source)
+    }
+
+    fn debit_points(&mut self, account_id: &AccountId, amount: u32, source: PointsSource) -> PointsResponse {
+        self.change_points(account_id, PointsEntryKind::Debit, amount, source)
+    }
+
+    // The only writer of reward_points: every balance change lands in the ledger
+    // and is announced as an event. Zero amounts leave no trace
+    fn change_points(
+        &mut self,
+        account_id: &AccountId,
+        kind: PointsEntryKind,
+        amount: u32,
+        source: PointsSource
+    ) -> PointsResponse {
+        let old_balance = self.reward_points.get(account_id).unwrap_or(0);
+        if amount == 0 {
+            return Response::Success(old_balance);
         }
 
-        let current_points = match self.get_reward_points(&account_id) {
-            Response::Success(points) => points,
-            Response::Error(err) => return Response::Error(err)
+        let new_balance = match kind.apply(old_balance, amount) {
+            Ok(balance) => balance,
+            Err(e) => return Response::Error(e.into())
         };
+        self.reward_points.insert(account_id, &new_balance);
 
-        if points > 0 {
-            match current_points.checked_add(points) {
-                Some(new_points) => {
-                    self.reward_points.insert(&account_id, &new_points);
-                    Response::Success(new_points)
-                },
-                None => Response::Error(ContractError::Operation("Points addition would overflow".to_string()))
-            }
-        } else {
-            let points_to_subtract = points.wrapping_neg();
-            if current_points < points_to_subtract {
-                return Response::Error(ContractError::Operation(
-                    format!("Insufficient points: has {}, needs {}", current_points, points_to_subtract)
-                ));
-            }
-            let new_points = current_points - points_to_subtract;
-            self.reward_points.insert(&account_id, &new_points);
-            Response::Success(new_points)
-        }
+        let mut ledger = self.points_ledger
+            .get(account_id)
+            .unwrap_or_else(|| Vector::new(nested_prefix(prefix::POINTS_LEDGER, account_id)));
+        ledger.push(&PointsEntry::new(kind, source.clone(), amount, env::block_timestamp(), new_balance));
+        self.points_ledger.insert(account_id, &ledger);
+
+        ContractEvent::PointsChanged {
+            account_id: account_id.clone(),
+            old_balance,
+            new_balance,
+            kind,
+            source,
+            amount,
+        }.emit();
+        Response::Success(new_balance)
     }
 
-    // === Task Management === 
-    pub fn get_tasks_by_owner(&self, owner_id: AccountId) -> TaskListResponse {
-        let task_set = match self.tasks_per_owner.get(&owner_id) {
-            Some(set) => set,
-            None => return Response::Error(ContractError::NotFound(
-                "Task".to_string(),
-                format!("No tasks found for {}", owner_id)
-            ))
-        };
+    // The per-owner nonce keeps ids unique within a block; the timestamp keeps
+    // them distinct from ids issued before the nonce existed
+    fn next_id(&mut self, entity: &str, owner_id: &AccountId) -> String {
+        let nonce = self.id_nonces.get(owner_id).unwrap_or(0) + 1;
+        self.id_nonces.insert(owner_id, &nonce);
+        format!("{}-{}-{}-{}", entity, owner_id, env::block_timestamp(), nonce)
+    }
 
-        let tasks: Vec<Task> = task_set
-            .iter()
-            .filter_map(|task_id| self.tasks.get(&task_id))
-            .collect();
+    // Entry point of every user mutation. Collections write through to storage
+    // immediately, so the usage delta around the action is exactly what it added
+    // or freed for the caller
+    fn mutate<T>(
+        &mut self,
+        action: impl FnOnce(&mut Self) -> Response<T, ContractError>,
+    ) -> Response<T, ContractError> {
+        self.mutate_for(env::predecessor_account_id(), action)
+    }
 
-        if tasks.is_empty() {
-            return Response::Error(ContractError::NotFound(
-                "Task".to_string(),
-                format!("No tasks found for {}", owner_id)
-            ));
+    // Same as mutate, but books the storage against the given account
+    fn mutate_for<T>(
+        &mut self,
+        account_id: AccountId,
+        action: impl FnOnce(&mut Self) -> Response<T, ContractError>,
+    ) -> Response<T, ContractError> {
+        if self.paused {
+            return Response::Error(ContractError::Paused);
         }
 
-        Response::Success(tasks)
+        let initial_usage = env::storage_usage();
+        let response = action(self);
+
+        if let Err(e) = self.settle_storage(&account_id, initial_usage) {
+            // Returning an error would still commit the writes, so abort the receipt instead
+            env::panic_str(&ContractError::from(e).to_string());
+        }
+        response
     }
 
-    pub fn get_incomplete_tasks(&self, owner_id: AccountId) -> TaskListResponse {
-        let all_tasks = match self.get_tasks_by_owner(owner_id.clone()) {
-            Response::Success(tasks) => tasks,
-            Response::Error(err) => return Response::Error(err),
+    fn settle_storage(&mut self, account_id: &AccountId, initial_usage: u64) -> Result<(), StorageError> {
+        let final_usage = env::storage_usage();
+        if final_usage == initial_usage {
+            return Ok(());
+        }
+
+        let mut account = match self.storage_accounts.get(account_id) {
+            Some(account) => account,
+            None if final_usage < initial_usage => return Ok(()),
+            None => return Err(StorageError::NotRegistered { account_id: account_id.clone() })
         };
 
-        let incomplete_tasks: Vec<Task> = all_tasks
-            .into_iter()
-            .filter(|task| task.state != TaskState::Completed)
-            .collect();
+        account.bytes_used = (account.bytes_used + final_usage).saturating_sub(initial_usage);
+        let required = account.storage_cost();
+        if required > account.deposit {
+            return Err(StorageError::InsufficientBalance {
+                required,
+                available: account.deposit,
+            });
+        }
 
-        if incomplete_tasks.is_empty() {
-            return Response::Error(ContractError::NotFound(
-                "Task".to_string(),
-                format!("No incomplete tasks found for {}", owner_id)
-            ));
+        self.storage_accounts.insert(account_id, &account);
+        Ok(())
+    }
+
+    fn owns_entries(&self, account_id: &AccountId) -> bool {
+        let tasks = self.tasks_per_owner.get(account_id).map_or(0, |set| set.len());
+        let habits = self.habits_per_owner.get(account_id).map_or(0, |set| set.len());
+        let rewards = self.rewards_per_owner.get(account_id).map_or(0, |set| set.len());
+        let time_slots = self.time_slots_per_owner.get(account_id).map_or(0, |set| set.len());
+        tasks + habits + rewards + time_slots > 0
+    }
+
+    // Everything keyed by the account itself; only valid once it owns no entries
+    fn remove_account_records(&mut self, account_id: &AccountId) {
+        self.tasks_per_owner.remove(account_id);
+        self.habits_per_owner.remove(account_id);
+        self.rewards_per_owner.remove(account_id);
+        self.time_slots_per_owner.remove(account_id);
+        for state in TaskState::ALL {
+            if let Some(mut state_tree) = self.tasks_by_state.remove(&(account_id.clone(), state)) {
+                state_tree.clear();
+            }
         }
+        if let Some(mut ledger) = self.points_ledger.remove(account_id) {
+            ledger.clear();
+        }
+        self.account_settings.remove(account_id);
+        self.id_nonces.remove(account_id);
+        self.reward_points.remove(account_id);
+        self.penalty_policies.remove(account_id);
+        self.streak_freezes.remove(account_id);
+    }
 
-        Response::Success(incomplete_tasks)
+    fn load_task(&self, task_id: &TaskId) -> Option<Task> {
+        self.tasks.get(task_id).map(Task::from)
     }
 
-    pub fn get_completed_tasks(&self, owner_id: AccountId) -> TaskListResponse {
-        let all_tasks = match self.get_tasks_by_owner(owner_id.clone()) {
-            Response::Success(tasks) => tasks,
-            Response::Error(err) => return Response::Error(err),
-        };
+    fn load_habit(&self, habit_id: &HabitId) -> Option<Habit> {
+        self.habits.get(habit_id).map(Habit::from)
+    }
 
-        let completed_tasks: Vec<Task> = all_tasks
-            .into_iter()
-            .filter(|task| task.state == TaskState::Completed)
-            .collect();
+    fn load_reward(&self, reward_id: &RewardId) -> Option<Reward> {
+        self.rewards.get(reward_id).map(Reward::from)
+    }
 
-        if completed_tasks.is_empty() {
-            return Response::Error(ContractError::NotFound(
-                "Task".to_string(),
-                format!("No completed tasks found for {}", owner_id)
-            ));
+    fn load_time_slot(&self, slot_id: &TimeSlotId) -> Option<TimeSlot> {
+        self.time_slots.get(slot_id).map(TimeSlot::from)
+    }
+
+    // Every task write goes through here, so creation and state change events
+    // are emitted in one place
+    fn save_task(&mut self, task: &Task) {
+        let previous = self.tasks.insert(&task.id, &task.clone().into()).map(Task::from);
+        self.update_task_indices(previous.as_ref(), Some(task));
+
+        match previous {
+            None => ContractEvent::TaskCreated {
+                task_id: task.id.clone(),
+                owner_id: task.get_owner_id().clone(),
+                parent_task_id: task.parent_task_id.clone(),
+                deadline: task.deadline,
+                reward_points: task.reward_points,
+            }.emit(),
+            Some(previous) if previous.state != task.state => ContractEvent::TaskStateChanged {
+                task_id: task.id.clone(),
+                owner_id: task.get_owner_id().clone(),
+                old_state: previous.state,
+                new_state: task.state,
+            }.emit(),
+            Some(_) => (),
         }
+    }
 
-        Response::Success(completed_tasks)
+    // Also drops the habit the task backs, so no habit outlives its task
+    fn remove_task_record(&mut self, task_id: &TaskId) {
+        let removed = self.tasks.remove(task_id).map(Task::from);
+        self.update_task_indices(removed.as_ref(), None);
+        self.task_completions.remove(task_id);
+
+        if let Some(habit) = self.habit_by_task.get(task_id).and_then(|habit_id| self.load_habit(&habit_id)) {
+            self.remove_habit_record(&habit);
+        }
+
+        if let Some(task) = removed {
+            ContractEvent::TaskDeleted {
+                task_id: task.id.clone(),
+                owner_id: task.get_owner_id().clone(),
+            }.emit();
+        }
     }
 
-    pub fn add_task(
-        &mut self,
-        title: String,
-        description: String,
-        priority: Priority,
-        deadline: u64,
-        estimated_time: u32,
-        time_slots: Option<Vec<TaskTimeSlot>>,
-        parent_task_id: Option<TaskId>,
-        recurrence_pattern: Option<RecurrencePattern>,
-    ) -> TaskActionResponse {
-        let owner_id = env::predecessor_account_id();
-        
-        if let Some(ref parent_id) = parent_task_id {
-            let parent_task = match self.tasks.get(parent_id) {
-                Some(task) => task,
-                None => return Response::Error(ContractError::NotFound(
-                    "Parent Task".to_string(),
-                    format!("Parent task {} not found", parent_id)
-                ))
-            };
-    
-            if parent_task.get_owner_id() != &owner_id {
-                return Response::Error(ContractError::AccessError(OwnershipError::NotOwner));
+    fn update_task_indices(&mut self, previous: Option<&Task>, current: Option<&Task>) {
+        let state_key = |task: &Task| (task.get_owner_id().clone(), task.state);
+        let deadline_key = |task: &Task| (task.deadline, task.id.clone());
+
+        if let (Some(previous), Some(current)) = (previous, current) {
+            if state_key(previous) == state_key(current) && previous.deadline == current.deadline {
+                return;
             }
         }
-    
-        let mut task = match Task::new(
-            title,
-            description,
-            priority,
-            deadline,
-            estimated_time,
-            time_slots.unwrap_or_default(),
-            owner_id.clone()
-        ) {
-            Ok(task) => task,
-            Err(e) => return Response::Error(e.into())
-        };
-    
-        if let Some(ref parent_id) = parent_task_id {
-            task.parent_task_id = Some(parent_id.clone());
-        }
-    
-        let task_id = task.id.clone();
-        self.tasks.insert(&task_id, &task);
-        self.add_to_owner_index(&owner_id, &task_id, IndexType::Task);
-        
-        if let Some(recurrence) = recurrence_pattern {
-            match Habit::new(task_id.clone(), recurrence, owner_id.clone()) {
-                Ok(habit) => {
-                    let habit_id = habit.id.clone();
-                    self.habits.insert(&habit_id, &habit);
-                    self.add_to_owner_index(&owner_id, &habit_id, IndexType::Habit);
-                },
-                Err(e) => return Response::Error(e.into())
+
+        if let Some(task) = previous {
+            let key = state_key(task);
+            if let Some(mut state_tree) = self.tasks_by_state.get(&key) {
+                state_tree.remove(&deadline_key(task));
+                self.tasks_by_state.insert(&key, &state_tree);
             }
         }
-        
-        if let Some(parent_id) = parent_task_id {
-            let mut parent_task = match self.tasks.get(&parent_id) {
-                Some(task) => task,
-                None => return Response::Error(ContractError::NotFound(
-                    "Parent Task".to_string(),
-                    format!("Parent task {} not found", parent_id)
-                ))
+        if let Some(task) = current {
+            let key = state_key(task);
+            let mut state_tree = self.tasks_by_state
+                .get(&key)
+                .unwrap_or_else(|| TreeMap::new(state_index_prefix(&key.0, key.1)));
+            state_tree.insert(&deadline_key(task), &());
+            self.tasks_by_state.insert(&key, &state_tree);
+        }
+    }
+
+    // Walks the owner's tasks in the given states by deadline, merging the
+    // per-state trees, until `visit` returns false
+    fn walk_by_deadline<F>(&self, owner_id: &AccountId, states: &[TaskState], descending: bool, mut visit: F)
+    where
+        F: FnMut(u64, TaskId) -> bool,
+    {
+        let state_trees: Vec<TreeMap<(u64, TaskId), ()>> = states
+            .iter()
+            .filter_map(|state| self.tasks_by_state.get(&(owner_id.clone(), *state)))
+            .collect();
+        let mut walks: Vec<Box<dyn Iterator<Item = (u64, TaskId)> + '_>> = state_trees
+            .iter()
+            .map(|state_tree| -> Box<dyn Iterator<Item = (u64, TaskId)> + '_> {
+                if descending {
+                    Box::new(state_tree.iter_rev().map(|(key, _)| key))
+                } else {
+                    Box::new(state_tree.iter().map(|(key, _)| key))
+                }
+            })
+            .collect();
+        let mut heads: Vec<Option<(u64, TaskId)>> = walks.iter_mut().map(|walk| walk.next()).collect();
+
+        loop {
+            let next = heads
+                .iter()
+                .enumerate()
+                .filter_map(|(position, head)| head.as_ref().map(|key| (position, key)))
+                .min_by(|(_, a), (_, b)| if descending { b.cmp(a) } else { a.cmp(b) })
+                .map(|(position, _)| position);
+            let position = match next {
+                Some(position) => position,
+                None => return
             };
-    
-            match parent_task.add_subtask(task_id.clone()) {
-                Ok(_) => {
-                    self.tasks.insert(&parent_id, &parent_task);
-                    Response::Success(task_id)
-                },
-                Err(e) => Response::Error(e.into())
+
+            let head = std::mem::replace(&mut heads[position], walks[position].next());
+            if let Some((deadline, task_id)) = head {
+                if !visit(deadline, task_id) {
+                    return;
+                }
             }
-        } else {
-            Response::Success(task_id)
         }
     }
 
-    pub fn update_task(
-        &mut self,
-        task_id: TaskId,
-        title: String,
-        description: String,
-        priority: Priority,
-        deadline: u64,
-        estimated_time: u32,
-        time_slots: Option<Vec<TaskTimeSlot>>,
-    ) -> TaskActionResponse {
-        let mut task = match self.tasks.get(&task_id) {
-            Some(t) => t,
-            None => return Response::Error(ContractError::NotFound(
-                "Task".to_string(),
-                format!("Task {} not found", task_id)
-            ))
-        };
-    
-        if let Err(e) = task.validate_ownership() {
-            return Response::Error(ContractError::AccessError(e));
+    // Indexes tasks and habits stored before the secondary indices existed
+    pub fn rebuild_indices(&mut self) -> Response<u64, ContractError> {
+        self.mutate(|contract| contract.internal_rebuild_indices())
+    }
+
+    fn internal_rebuild_indices(&mut self) -> Response<u64, ContractError> {
+        let owner_id = env::predecessor_account_id();
+        let tasks: Vec<Task> = self.tasks_per_owner
+            .get(&owner_id)
+            .map(|task_set| task_set.iter().filter_map(|task_id| self.load_task(&task_id)).collect())
+            .unwrap_or_default();
+        let habits: Vec<Habit> = self.habits_per_owner
+            .get(&owner_id)
+            .map(|habit_set| habit_set.iter().filter_map(|habit_id| self.load_habit(&habit_id)).collect())
+            .unwrap_or_default();
+
+        for task in &tasks {
+            self.update_task_indices(None, Some(task));
         }
-    
-        task.title = title;
-        task.description = description;
-        task.priority = priority;
-        task.deadline = deadline;
-        task.estimated_time = estimated_time;
-        if let Some(slots) = time_slots {
-            task.time_slots = slots;
+        for habit in &habits {
+            self.habit_by_task.insert(&habit.task_id, &habit.id);
         }
-    
-        task.reward_points = Task::calculate_reward_points(estimated_time, priority);
-    
-        if let Err(e) = task.validate() {
+
+        Response::Success((tasks.len() + habits.len()) as u64)
+    }
+
+    // === Administration ===
+    pub fn get_roles(&self) -> RoleListResponse {
+        Response::Success(self.roles.to_vec())
+    }
+
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) -> Response<AccountId, ContractError> {
+        if let Err(e) = self.require_role(Role::Admin) {
+            return Response::Error(e);
+        }
+
+        self.roles.insert(&account_id, &role);
+        ContractEvent::RoleGranted {
+            account_id: account_id.clone(),
+            role,
+            granted_by: env::predecessor_account_id(),
+        }.emit();
+        Response::Success(account_id)
+    }
+
+    pub fn revoke_role(&mut self, account_id: AccountId) -> Response<AccountId, ContractError> {
+        if let Err(e) = self.require_role(Role::Admin) {
+            return Response::Error(e);
+        }
+
+        let admin_count = self.roles.values().filter(|role| *role == Role::Admin).count();
+        if self.roles.get(&account_id) == Some(Role::Admin) && admin_count == 1 {
+            return Response::Error(ContractError::Operation("Cannot revoke the last admin".to_string()));
+        }
+
+        match self.roles.remove(&account_id) {
+            Some(_) => {
+                ContractEvent::RoleRevoked {
+                    account_id: account_id.clone(),
+                    revoked_by: env::predecessor_account_id(),
+                }.emit();
+                Response::Success(account_id)
+            },
+            None => Response::Error(ContractError::NotFound("Role".to_string(), account_id.to_string()))
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Moderators can stop the contract in an emergency; only admins resume it
+    pub fn pause(&mut self) -> Response<bool, ContractError> {
+        if let Err(e) = self.require_role(Role::Moderator) {
+            return Response::Error(e);
+        }
+
+        self.paused = true;
+        ContractEvent::ContractPaused { account_id: env::predecessor_account_id() }.emit();
+        Response::Success(self.paused)
+    }
+
+    pub fn unpause(&mut self) -> Response<bool, ContractError> {
+        if let Err(e) = self.require_role(Role::Admin) {
+            return Response::Error(e);
+        }
+
+        self.paused = false;
+        ContractEvent::ContractUnpaused { account_id: env::predecessor_account_id() }.emit();
+        Response::Success(self.paused)
+    }
+
+    pub fn get_config(&self) -> ContractConfigResponse {
+        Response::Success(self.config.clone())
+    }
+
+    pub fn update_config(&mut self, config: ContractConfig) -> ContractConfigResponse {
+        if let Err(e) = self.require_role(Role::Admin) {
+            return Response::Error(e);
+        }
+
+        if let Err(e) = config.validate() {
+            return Response::Error(e.into());
+        }
+
+        self.config = config;
+        ContractEvent::ConfigUpdated { account_id: env::predecessor_account_id() }.emit();
+        Response::Success(self.config.clone())
+    }
+
+    fn require_role(&self, required: Role) -> Result<(), ContractError> {
+        let account_id = env::predecessor_account_id();
+        match self.roles.get(&account_id) {
+            Some(role) if role.grants(required) => Ok(()),
+            _ => Err(ContractError::Unauthorized(account_id.to_string(), required.to_string()))
+        }
+    }
+
+    fn assert_not_paused(&self) {
+        if self.paused {
+            env::panic_str(&ContractError::Paused.to_string());
+        }
+    }
+
+    // === Storage Management (NEP-145) ===
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.assert_not_paused();
+        let amount = env::attached_deposit().as_yoctonear();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+
+        if let Some(mut account) = self.storage_accounts.get(&account_id) {
+            if registration_only {
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(amount));
+                }
+            } else {
+                account.deposit += amount;
+                self.storage_accounts.insert(&account_id, &account);
+            }
+            return account.balance();
+        }
+
+        let min_balance = self.storage_balance_bounds().min.0;
+        if amount < min_balance {
+            env::panic_str(&format!(
+                "The attached deposit is less than the minimum storage balance of {}", min_balance
+            ));
+        }
+
+        let deposit = if registration_only { min_balance } else { amount };
+        let initial_usage = env::storage_usage();
+        let mut account = StorageAccount::new(deposit);
+        self.storage_accounts.insert(&account_id, &account);
+        account.bytes_used = env::storage_usage() - initial_usage;
+        self.storage_accounts.insert(&account_id, &account);
+
+        let refund = amount - deposit;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund));
+        }
+        account.balance()
+    }
+
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        near_sdk::assert_one_yocto();
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id();
+
+        let mut account = match self.storage_accounts.get(&account_id) {
+            Some(account) => account,
+            None => env::panic_str(&StorageError::NotRegistered { account_id }.to_string())
+        };
+
+        let available = account.available();
+        let amount = amount.map_or(available, |amount| amount.0);
+        if amount > available {
+            env::panic_str(&StorageError::InsufficientBalance { required: amount, available }.to_string());
+        }
+
+        account.deposit -= amount;
+        self.storage_accounts.insert(&account_id, &account);
+        if amount > 0 {
+            Promise::new(account_id).transfer(NearToken::from_yoctonear(amount));
+        }
+        account.balance()
+    }
+
+    // Force deletion is not supported: entries must be deleted through their own
+    // methods first, since they carry indices and dependencies that need unwinding.
+    // Whatever the account still holds besides them, points and freezes included,
+    // is removed with the registration
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        near_sdk::assert_one_yocto();
+        self.assert_not_paused();
+        if force == Some(true) {
+            env::panic_str("Force unregistration is not supported, delete the account's entries first");
+        }
+        let account_id = env::predecessor_account_id();
+
+        let account = match self.storage_accounts.get(&account_id) {
+            Some(account) => account,
+            None => return false
+        };
+
+        if self.owns_entries(&account_id) {
+            env::panic_str(&format!("Cannot unregister {} while it still owns entries", account_id));
+        }
+
+        self.remove_account_records(&account_id);
+        self.storage_accounts.remove(&account_id);
+        Promise::new(account_id).transfer(NearToken::from_yoctonear(account.deposit));
+        true
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(self.config.account_min_storage_bytes as u128 * env::storage_byte_cost().as_yoctonear()),
+            max: None,
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_accounts.get(&account_id).map(|account| account.balance())
+    }
+
+    pub fn get_storage_report(&self, account_id: AccountId) -> StorageReportResponse {
+        let task_ids = self.tasks_per_owner.get(&account_id).map_or_else(Vec::new, |set| set.to_vec());
+        let task_bytes = task_ids.iter().map(|task_id| {
+            self.load_task(task_id).map_or(0, |task| task.calculate_storage_metrics().total_bytes)
+                + lookup_entry_bytes(prefix::TASK_COMPLETIONS, task_id, self.task_completions.get(task_id).as_ref())
+                + lookup_entry_bytes(prefix::TASK_BLOCKERS, task_id, self.task_blockers.get(task_id).as_ref())
+                + lookup_entry_bytes(prefix::TASK_DEPENDENTS, task_id, self.task_dependents.get(task_id).as_ref())
+        }).sum();
+
+        let tasks = EntityStorage::new(task_ids.len() as u64, task_bytes);
+        let habits = Self::entity_storage::<Habit, _>(&self.habits, self.habits_per_owner.get(&account_id));
+        let rewards = Self::entity_storage::<Reward, _>(&self.rewards, self.rewards_per_owner.get(&account_id));
+        let time_slots = Self::entity_storage::<TimeSlot, _>(
+            &self.time_slots,
+            self.time_slots_per_owner.get(&account_id),
+        );
+
+        let ledger = self.points_ledger.get(&account_id);
+        let ledger_prefix_len = nested_prefix(prefix::POINTS_LEDGER, &account_id).len() as u64;
+        let ledger_bytes = ledger.as_ref().map_or(0, |ledger| {
+            ledger.iter().map(|entry| vector_entry_bytes(ledger_prefix_len, borsh_len(&entry))).sum()
+        }) + lookup_entry_bytes(prefix::POINTS_LEDGER, &account_id, ledger.as_ref());
+        let points_ledger = EntityStorage::new(ledger.as_ref().map_or(0, |ledger| ledger.len()), ledger_bytes);
+
+        let (history_count, history_bytes) = self.habits_per_owner.get(&account_id)
+            .map_or((0, 0), |habit_ids| habit_ids.iter().fold((0, 0), |(count, bytes), habit_id| {
+                let Some(history) = self.habit_history.get(&habit_id) else {
+                    return (count, bytes);
+                };
+                let history_prefix_len = habit_history_prefix(&habit_id).len() as u64;
+                let entry_bytes: u64 = history.iter()
+                    .map(|entry| vector_entry_bytes(history_prefix_len, borsh_len(&entry)))
+                    .sum();
+                (
+                    count + history.len(),
+                    bytes + entry_bytes + lookup_entry_bytes(prefix::HABIT_HISTORY, &habit_id, Some(&history)),
+                )
+            }));
+        let habit_history = EntityStorage::new(history_count, history_bytes);
+
+        let storage_account = self.storage_accounts.get(&account_id);
+        let mut account_bytes =
+            lookup_entry_bytes(prefix::TASKS_PER_OWNER, &account_id, self.tasks_per_owner.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::HABITS_PER_OWNER, &account_id, self.habits_per_owner.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::REWARDS_PER_OWNER, &account_id, self.rewards_per_owner.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::TIME_SLOTS_PER_OWNER, &account_id, self.time_slots_per_owner.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::ACCOUNT_SETTINGS, &account_id, self.account_settings.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::PENALTY_POLICIES, &account_id, self.penalty_policies.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::STREAK_FREEZES, &account_id, self.streak_freezes.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::REWARD_POINTS, &account_id, self.reward_points.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::ID_NONCES, &account_id, self.id_nonces.get(&account_id).as_ref())
+            + lookup_entry_bytes(prefix::STORAGE_ACCOUNTS, &account_id, storage_account.as_ref());
+        for state in TaskState::ALL {
+            let key = (account_id.clone(), state);
+            account_bytes += lookup_entry_bytes(prefix::TASKS_BY_STATE, &key, self.tasks_by_state.get(&key).as_ref());
+        }
+        let account = EntityStorage::new(1, account_bytes);
+
+        let total_bytes = tasks.bytes + habits.bytes + rewards.bytes + time_slots.bytes
+            + points_ledger.bytes + habit_history.bytes + account.bytes;
+        Response::Success(StorageReport {
+            total_cost: EntityStorage::new(0, total_bytes).cost,
+            total_bytes,
+            tasks,
+            habits,
+            rewards,
+            time_slots,
+            points_ledger,
+            habit_history,
+            account,
+            measured_bytes: storage_account.as_ref().map_or(0, |account| account.bytes_used),
+            balance: storage_account.map(|account| account.balance()),
+        })
+    }
+
+    fn entity_storage<E, V>(entities: &UnorderedMap<String, V>, ids: Option<UnorderedSet<String>>) -> EntityStorage
+    where
+        E: Storable + From<V>,
+        V: BorshSerialize + BorshDeserialize,
+    {
+        let ids = ids.map_or_else(Vec::new, |set| set.to_vec());
+        let bytes = ids.iter()
+            .filter_map(|id| entities.get(id))
+            .map(|entity| E::from(entity).calculate_storage_metrics().total_bytes)
+            .sum();
+        EntityStorage::new(ids.len() as u64, bytes)
+    }
+
+    // === Account Settings ===
+    pub fn get_account_settings(&self, account_id: AccountId) -> AccountSettingsResponse {
+        Response::Success(self.account_settings.get(&account_id).unwrap_or_default())
+    }
+
+    pub fn set_utc_offset(&mut self, utc_offset_minutes: i32) -> AccountSettingsResponse {
+        self.mutate(|contract| contract.internal_set_utc_offset(utc_offset_minutes))
+    }
+
+    fn internal_set_utc_offset(&mut self, utc_offset_minutes: i32) -> AccountSettingsResponse {
+        let account_id = env::predecessor_account_id();
+
+        let mut settings = self.account_settings.get(&account_id).unwrap_or_default();
+        settings.utc_offset_minutes = utc_offset_minutes;
+
+        if let Err(e) = settings.validate() {
+            return Response::Error(e.into());
+        }
+
+        self.account_settings.insert(&account_id, &settings);
+        ContractEvent::SettingsUpdated {
+            account_id,
+            utc_offset_minutes: settings.utc_offset_minutes,
+        }.emit();
+        Response::Success(settings)
+    }
+
+    pub fn get_penalty_policy(&self, account_id: AccountId) -> PenaltyPolicyResponse {
+        Response::Success(self.penalty_policies.get(&account_id))
+    }
+
+    // Passing no policy turns penalties off again
+    pub fn set_penalty_policy(&mut self, policy: Option<PenaltyPolicy>) -> PenaltyPolicyResponse {
+        self.mutate(|contract| contract.internal_set_penalty_policy(policy))
+    }
+
+    fn internal_set_penalty_policy(&mut self, policy: Option<PenaltyPolicy>) -> PenaltyPolicyResponse {
+        let account_id = env::predecessor_account_id();
+
+        match &policy {
+            Some(policy) => {
+                if let Err(e) = policy.validate() {
+                    return Response::Error(e.into());
+                }
+                self.penalty_policies.insert(&account_id, policy);
+            },
+            None => {
+                self.penalty_policies.remove(&account_id);
+            }
+        }
+
+        ContractEvent::PenaltyPolicyUpdated {
+            account_id,
+            policy: policy.clone(),
+        }.emit();
+        Response::Success(policy)
+    }
+
+    fn utc_offset(&self, account_id: &AccountId) -> i32 {
+        self.account_settings
+            .get(account_id)
+            .map_or(0, |settings| settings.utc_offset_minutes)
+    }
+
+    // === Task Management === 
+    pub fn get_tasks_by_owner(&self, owner_id: AccountId) -> TaskListResponse {
+        let task_set = match self.tasks_per_owner.get(&owner_id) {
+            Some(set) => set,
+            None => return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("No tasks found for {}", owner_id)
+            ))
+        };
+
+        let tasks: Vec<Task> = task_set
+            .iter()
+            .filter_map(|task_id| self.load_task(&task_id))
+            .collect();
+
+        if tasks.is_empty() {
+            return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("No tasks found for {}", owner_id)
+            ));
+        }
+
+        Response::Success(tasks)
+    }
+
+    pub fn query_tasks(&self, owner_id: AccountId, query: Option<TaskQuery>) -> TaskPageResponse {
+        let query = query.unwrap_or_default();
+        let states = match query.state {
+            Some(state) => vec![state],
+            None => TaskState::ALL.to_vec(),
+        };
+        let descending = query.descending.unwrap_or(false);
+
+        // Only deadlines are indexed, other sort keys load every candidate
+        if query.sort_by.unwrap_or(TaskSortKey::Deadline) != TaskSortKey::Deadline {
+            let mut tasks = Vec::new();
+            self.walk_by_deadline(&owner_id, &states, descending, |deadline, task_id| {
+                if query.matches_deadline(deadline) {
+                    tasks.extend(self.load_task(&task_id));
+                }
+                true
+            });
+            return Response::Success(query.apply(tasks, &self.config));
+        }
+
+        let (from_index, limit) = query.page_bounds(&self.config);
+        let mut tasks = Vec::new();
+
+        // The state trees give the total, so the walk stops once the page is full
+        if query.filters_by_state_only() {
+            let total = states
+                .iter()
+                .map(|state| self.tasks_by_state.get(&(owner_id.clone(), *state)).map_or(0, |tree| tree.len()))
+                .sum();
+            let mut position = 0;
+            self.walk_by_deadline(&owner_id, &states, descending, |_, task_id| {
+                if tasks.len() >= limit {
+                    return false;
+                }
+                if position >= from_index {
+                    tasks.extend(self.load_task(&task_id));
+                }
+                position += 1;
+                true
+            });
+            return Response::Success(TaskPage { tasks, total });
+        }
+
+        // Other filters are counted over the deadline range, loading only tasks inside it
+        let mut total = 0;
+        self.walk_by_deadline(&owner_id, &states, descending, |deadline, task_id| {
+            if query.past_deadline_range(deadline) {
+                return false;
+            }
+            let task = query.matches_deadline(deadline)
+                .then(|| self.load_task(&task_id))
+                .flatten()
+                .filter(|task| query.matches(task));
+            if let Some(task) = task {
+                if total >= from_index && tasks.len() < limit {
+                    tasks.push(task);
+                }
+                total += 1;
+            }
+            true
+        });
+
+        Response::Success(TaskPage { tasks, total: total as u64 })
+    }
+
+    pub fn get_tasks_by_state(
+        &self,
+        owner_id: AccountId,
+        state: TaskState,
+        from_index: Option<u64>,
+        limit: Option<u64>
+    ) -> TaskPageResponse {
+        let query = TaskQuery { from_index, limit, ..Default::default() };
+        let (from_index, limit) = query.page_bounds(&self.config);
+
+        let state_tree = match self.tasks_by_state.get(&(owner_id, state)) {
+            Some(tree) => tree,
+            None => return Response::Success(TaskPage { tasks: Vec::new(), total: 0 })
+        };
+
+        let tasks = state_tree
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .filter_map(|((_, task_id), _)| self.load_task(&task_id))
+            .collect();
+
+        Response::Success(TaskPage { tasks, total: state_tree.len() })
+    }
+
+    pub fn get_upcoming_tasks(&self, owner_id: AccountId, limit: Option<u64>) -> TaskListResponse {
+        let query = TaskQuery { limit, ..Default::default() };
+        let (_, limit) = query.page_bounds(&self.config);
+        let current_time = env::block_timestamp();
+
+        let mut tasks = Vec::new();
+        self.walk_by_deadline(&owner_id, &[TaskState::Created, TaskState::InProgress], false, |deadline, task_id| {
+            if tasks.len() >= limit {
+                return false;
+            }
+            if deadline >= current_time {
+                tasks.extend(self.load_task(&task_id));
+            }
+            true
+        });
+
+        Response::Success(tasks)
+    }
+
+    pub fn get_overdue_tasks(&self, owner_id: AccountId, limit: Option<u64>) -> TaskListResponse {
+        let query = TaskQuery { limit, ..Default::default() };
+        let (_, limit) = query.page_bounds(&self.config);
+        let current_time = env::block_timestamp();
+
+        // Past their deadline, whether or not a sweep has marked them yet
+        let states = [TaskState::Created, TaskState::InProgress, TaskState::Overdue];
+        let mut tasks = Vec::new();
+        self.walk_by_deadline(&owner_id, &states, false, |deadline, task_id| {
+            if deadline >= current_time || tasks.len() >= limit {
+                return false;
+            }
+            tasks.extend(self.load_task(&task_id));
+            true
+        });
+
+        Response::Success(tasks)
+    }
+
+    pub fn get_incomplete_tasks(&self, owner_id: AccountId) -> TaskListResponse {
+        let all_tasks = match self.get_tasks_by_owner(owner_id.clone()) {
+            Response::Success(tasks) => tasks,
+            Response::Error(err) => return Response::Error(err),
+        };
+
+        let incomplete_tasks: Vec<Task> = all_tasks
+            .into_iter()
+            .filter(|task| task.state != TaskState::Completed)
+            .collect();
+
+        if incomplete_tasks.is_empty() {
+            return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("No incomplete tasks found for {}", owner_id)
+            ));
+        }
+
+        Response::Success(incomplete_tasks)
+    }
+
+    pub fn get_completed_tasks(&self, owner_id: AccountId) -> TaskListResponse {
+        let all_tasks = match self.get_tasks_by_owner(owner_id.clone()) {
+            Response::Success(tasks) => tasks,
+            Response::Error(err) => return Response::Error(err),
+        };
+
+        let completed_tasks: Vec<Task> = all_tasks
+            .into_iter()
+            .filter(|task| task.state == TaskState::Completed)
+            .collect();
+
+        if completed_tasks.is_empty() {
+            return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("No completed tasks found for {}", owner_id)
+            ));
+        }
+
+        Response::Success(completed_tasks)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn internal_add_task(
+        &mut self,
+        title: String,
+        description: String,
+        priority: Priority,
+        deadline: u64,
+        estimated_time: u32,
+        time_slots: Option<Vec<TaskTimeSlot>>,
+        parent_task_id: Option<TaskId>,
+        recurrence_pattern: Option<RecurrencePattern>,
+        allow_overlap: Option<bool>,
+    ) -> TaskActionResponse {
+        let owner_id = env::predecessor_account_id();
+        
+        if let Some(ref parent_id) = parent_task_id {
+            let parent_task = match self.load_task(parent_id) {
+                Some(task) => task,
+                None => return Response::Error(ContractError::NotFound(
+                    "Parent Task".to_string(),
+                    format!("Parent task {} not found", parent_id)
+                ))
+            };
+    
+            if parent_task.get_owner_id() != &owner_id {
+                return Response::Error(ContractError::AccessError(OwnershipError::NotOwner));
+            }
+
+            let depth = self.task_depth(&parent_task) + 1;
+            if depth > self.config.max_subtask_depth as usize {
+                return Response::Error(TaskValidationError::Subtasks {
+                    reason: SubtaskError::MaxDepthExceeded,
+                    current_count: depth,
+                }.into());
+            }
+        }
+    
+        let task_id = self.next_id("task", &owner_id);
+        if self.load_task(&task_id).is_some() {
+            return Response::Error(ContractError::AlreadyExists("Task".to_string(), task_id));
+        }
+    
+        let mut task = match Task::new(
+            task_id,
+            title,
+            description,
+            priority,
+            deadline,
+            estimated_time,
+            time_slots.unwrap_or_default(),
+            owner_id.clone(),
+            &self.config
+        ) {
+            Ok(task) => task,
+            Err(e) => return Response::Error(e.into())
+        };
+    
+        if let Some(ref parent_id) = parent_task_id {
+            task.parent_task_id = Some(parent_id.clone());
+        }
+
+        if let Err(e) = self.check_schedule_conflicts(&task, allow_overlap) {
+            return Response::Error(e);
+        }
+    
+        let task_id = task.id.clone();
+        self.save_task(&task);
+        self.add_to_owner_index(&owner_id, &task_id, IndexType::Task);
+        
+        if let Some(recurrence) = recurrence_pattern {
+            let habit_id = self.next_id("habit", &owner_id);
+            if self.load_habit(&habit_id).is_some() {
+                return Response::Error(ContractError::AlreadyExists("Habit".to_string(), habit_id));
+            }
+
+            match Habit::new(habit_id, task_id.clone(), recurrence, owner_id.clone(), &self.config) {
+                Ok(habit) => self.insert_new_habit(habit),
+                Err(e) => return Response::Error(e.into())
+            }
+        }
+        
+        if let Some(parent_id) = parent_task_id {
+            let mut parent_task = match self.load_task(&parent_id) {
+                Some(task) => task,
+                None => return Response::Error(ContractError::NotFound(
+                    "Parent Task".to_string(),
+                    format!("Parent task {} not found", parent_id)
+                ))
+            };
+    
+            match parent_task.add_subtask(task_id.clone()) {
+                Ok(_) => {
+                    self.save_task(&parent_task);
+                    Response::Success(task_id)
+                },
+                Err(e) => Response::Error(e.into())
+            }
+        } else {
+            Response::Success(task_id)
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn internal_update_task(
+        &mut self,
+        task_id: TaskId,
+        title: String,
+        description: String,
+        priority: Priority,
+        deadline: u64,
+        estimated_time: u32,
+        time_slots: Option<Vec<TaskTimeSlot>>,
+        allow_overlap: Option<bool>,
+    ) -> TaskActionResponse {
+        let mut task = match self.load_task(&task_id) {
+            Some(t) => t,
+            None => return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("Task {} not found", task_id)
+            ))
+        };
+    
+        if let Err(e) = task.validate_ownership() {
+            return Response::Error(ContractError::AccessError(e));
+        }
+    
+        task.title = title;
+        task.description = description;
+        task.priority = priority;
+        task.deadline = deadline;
+        task.estimated_time = estimated_time;
+        if let Some(slots) = time_slots {
+            task.time_slots = slots;
+        }
+    
+        task.reward_points = Task::calculate_reward_points(estimated_time, priority);
+    
+        if let Err(e) = task.validate(&self.config) {
             return Response::Error(e.into());
         }
+
+        if let Err(e) = self.check_schedule_conflicts(&task, allow_overlap) {
+            return Response::Error(e);
+        }
     
-        self.tasks.insert(&task_id, &task);
+        self.save_task(&task);
+        ContractEvent::TaskUpdated {
+            task_id: task_id.clone(),
+            owner_id: task.get_owner_id().clone(),
+        }.emit();
         Response::Success(task_id)
     }
 
     pub fn complete_task(&mut self, task_id: TaskId) -> TaskActionResponse {
-        let mut task = match self.tasks.get(&task_id) {
+        self.mutate(|contract| contract.internal_complete_task(task_id))
+    }
+
+    fn internal_complete_task(&mut self, task_id: TaskId) -> TaskActionResponse {
+        let mut task = match self.load_task(&task_id) {
             Some(t) => t,
             None => return Response::Error(ContractError::NotFound(
                 "Task".to_string(),
@@ -586,28 +1560,47 @@ impl Contract {
         if let Err(e) = task.validate_ownership() {
             return Response::Error(ContractError::AccessError(e));
         }
+
+        // Every task completed along with this one has to be unblocked before any
+        // of them is written; blockers inside the tree complete together with it
+        let descendants: Vec<Task> = self.collect_descendants(&task)
+            .into_iter()
+            .filter(|subtask| subtask.state != TaskState::Completed)
+            .collect();
+        let completing: HashSet<TaskId> = descendants.iter()
+            .map(|subtask| subtask.id.clone())
+            .chain(std::iter::once(task_id.clone()))
+            .collect();
+        for pending in std::iter::once(&task).chain(&descendants) {
+            if let Err(e) = self.ensure_unblocked(pending, &completing) {
+                return Response::Error(e.into());
+            }
+        }
     
-        for subtask_id in &task.subtask_ids {
-            let mut subtask = match self.tasks.get(subtask_id) {
-                Some(t) => t,
-                None => return Response::Error(ContractError::NotFound(
-                    "Subtask".to_string(),
-                    format!("Subtask {} not found", subtask_id)
-                ))
-            };
-    
+        for mut subtask in descendants {
+            let earned_points = self.completion_points(&subtask);
             if let Err(e) = subtask.transition_to(TaskState::Completed) {
                 return Response::Error(e.into());
             }
     
-            match self.add_reward_points(subtask.get_owner_id().clone(), subtask.reward_points) {
+            match self.credit_points(
+                subtask.get_owner_id(),
+                earned_points,
+                PointsSource::Task(subtask.id.clone())
+            ) {
                 Response::Success(_) => (),
                 Response::Error(e) => return Response::Error(e)
             }
     
-            self.tasks.insert(subtask_id, &subtask);
+            self.save_task(&subtask);
+            ContractEvent::TaskCompleted {
+                task_id: subtask.id.clone(),
+                owner_id: subtask.get_owner_id().clone(),
+                reward_points: earned_points,
+            }.emit();
         }
     
+        let earned_points = self.completion_points(&task);
         if let Err(e) = task.transition_to(TaskState::Completed) {
             return Response::Error(e.into());
         }
@@ -619,71 +1612,72 @@ impl Contract {
         completions.push(current_time);
         self.task_completions.insert(&task_id, &completions);
     
-        let habit_id_option = self.habits.iter()
-            .find(|(_, habit)| habit.task_id == task_id)
-            .map(|(id, _)| id.clone());
+        let habit_option = self.habit_by_task
+            .get(&task_id)
+            .and_then(|habit_id| self.load_habit(&habit_id))
+            .filter(|habit| !habit.paused);
             
-        if let Some(habit_id) = habit_id_option {
-            let mut habit = self.habits.get(&habit_id).unwrap();
+        if let Some(mut habit) = habit_option {
+            let habit_id = habit.id.clone();
             
-            if habit.verify_streak_continuity() {
-                habit.increment_streak();
-            } else {
-                habit.reset_streak();
-            }
+            let utc_offset_minutes = self.utc_offset(task.get_owner_id());
+            let owner_id = habit.get_owner_id().clone();
+            let available_freezes = self.streak_freezes.get(&owner_id).unwrap_or(0);
+            let entries = habit.record_completion(current_time, utc_offset_minutes, available_freezes);
+            self.use_streak_freezes(&habit, &entries, available_freezes);
+            self.append_habit_history(&habit_id, &entries);
             
-            let new_deadline = match &habit.recurrence.frequency {
-                Frequency::Daily => {
-                    let interval = habit.recurrence.interval.unwrap_or(1);
-                    current_time + (interval as u64) * 24 * 60 * 60 * 1_000_000_000
+            // A recurrence past its until date or count leaves the task completed
+            match habit.next_deadline(current_time, utc_offset_minutes) {
+                Some(new_deadline) => {
+                    task.state = TaskState::Created;
+                    task.deadline = new_deadline;
+                    task.time_slots.clear();
+
+                    ContractEvent::HabitStreakUpdated {
+                        habit_id: habit_id.clone(),
+                        task_id: habit.task_id.clone(),
+                        owner_id: habit.get_owner_id().clone(),
+                        streak: habit.streak,
+                        next_deadline: new_deadline,
+                    }.emit();
                 },
-                Frequency::Custom => {
-                    if let Some(ref days) = habit.recurrence.specific_days {
-                        let seconds_per_day = 24 * 60 * 60;
-                        let current_days = (current_time / 1_000_000_000) / seconds_per_day;
-                        let current_day_of_week = ((current_days + 3) % 7) as usize;
-                        
-                        let day_mapping = [
-                            DayOfWeek::Monday, DayOfWeek::Tuesday, DayOfWeek::Wednesday,
-                            DayOfWeek::Thursday, DayOfWeek::Friday, DayOfWeek::Saturday, DayOfWeek::Sunday
-                        ];
-                        
-                        let mut days_until_next = 7;
-                        for day_offset in 1..=7 {
-                            let next_day_idx = (current_day_of_week + day_offset) % 7;
-                            let next_day = day_mapping[next_day_idx].clone();
-                            if days.contains(&next_day) {
-                                days_until_next = day_offset;
-                                break;
-                            }
-                        }
-                        
-                        current_time + (days_until_next as u64) * 24 * 60 * 60 * 1_000_000_000
-                    } else {
-                        current_time + 7 * 24 * 60 * 60 * 1_000_000_000
-                    }
+                None => {
+                    ContractEvent::HabitEnded {
+                        habit_id: habit_id.clone(),
+                        task_id: habit.task_id.clone(),
+                        owner_id: habit.get_owner_id().clone(),
+                        streak: habit.streak,
+                    }.emit();
                 }
-            };
-            
-            task.state = TaskState::Created;
-            task.deadline = new_deadline;
-            task.time_slots.clear();
-            
-            habit.task_id = task.id.clone();
-            self.habits.insert(&habit_id, &habit);
+            }
+            self.habits.insert(&habit_id, &habit.into());
         }
     
-        match self.add_reward_points(task.get_owner_id().clone(), task.reward_points) {
+        match self.credit_points(
+            task.get_owner_id(),
+            earned_points,
+            PointsSource::Task(task_id.clone())
+        ) {
             Response::Success(_) => (),
             Response::Error(e) => return Response::Error(e)
         }
     
-        self.tasks.insert(&task_id, &task);
+        self.save_task(&task);
+        ContractEvent::TaskCompleted {
+            task_id: task_id.clone(),
+            owner_id: task.get_owner_id().clone(),
+            reward_points: earned_points,
+        }.emit();
         Response::Success(task_id)
     }
 
     pub fn mark_task_overdue(&mut self, task_id: TaskId) -> TaskActionResponse {
-        let mut task = match self.tasks.get(&task_id) {
+        self.mutate(|contract| contract.internal_mark_task_overdue(task_id))
+    }
+
+    fn internal_mark_task_overdue(&mut self, task_id: TaskId) -> TaskActionResponse {
+        let task = match self.load_task(&task_id) {
             Some(t) => t,
             None => return Response::Error(ContractError::NotFound(
                 "Task".to_string(),
@@ -704,18 +1698,124 @@ impl Contract {
             ));
         }
         
+        self.mark_overdue(task)
+    }
+
+    // Anyone can move an account's past-deadline tasks to Overdue, oldest first.
+    // Storage is booked against the owner, who also pays the keeper fee from the
+    // available part of their deposit
+    pub fn sweep_overdue(&mut self, owner_id: AccountId, limit: Option<u64>) -> OverdueSweepResponse {
+        let keeper_id = env::predecessor_account_id();
+        let response = self.mutate_for(owner_id.clone(), |contract| contract.internal_sweep_overdue(&owner_id, limit));
+
+        match response {
+            Response::Success(mut sweep) => {
+                if keeper_id != owner_id {
+                    sweep.keeper_fee = U128(self.pay_keeper_fee(&owner_id, &keeper_id, sweep.swept.len() as u64));
+                }
+                ContractEvent::OverdueSwept {
+                    owner_id,
+                    keeper_id,
+                    task_count: sweep.swept.len() as u64,
+                    keeper_fee: sweep.keeper_fee,
+                }.emit();
+                Response::Success(sweep)
+            },
+            Response::Error(e) => Response::Error(e)
+        }
+    }
+
+    fn internal_sweep_overdue(&mut self, owner_id: &AccountId, limit: Option<u64>) -> OverdueSweepResponse {
+        let current_time = env::block_timestamp();
+        let limit = limit.unwrap_or(self.config.default_page_limit).min(self.config.max_page_limit) as usize;
+
+        // Overdue tasks have left these trees, so only unswept ones are walked
+        let mut due_ids: Vec<TaskId> = Vec::new();
+        self.walk_by_deadline(owner_id, &[TaskState::Created, TaskState::InProgress], false, |deadline, task_id| {
+            if deadline >= current_time || due_ids.len() >= limit {
+                return false;
+            }
+            due_ids.push(task_id);
+            true
+        });
+
+        let mut swept = Vec::with_capacity(due_ids.len());
+        for task_id in due_ids {
+            let task = match self.load_task(&task_id) {
+                Some(t) => t,
+                None => continue
+            };
+            match self.mark_overdue(task) {
+                Response::Success(task_id) => swept.push(task_id),
+                Response::Error(e) => return Response::Error(e)
+            }
+        }
+
+        Response::Success(OverdueSweep { swept, keeper_fee: U128(0) })
+    }
+
+    // Shared by mark_task_overdue and the sweeper
+    fn mark_overdue(&mut self, mut task: Task) -> TaskActionResponse {
         if let Err(e) = task.transition_to(TaskState::Overdue) {
             return Response::Error(e.into());
         }
         
         task.time_slots.clear();
         
-        self.tasks.insert(&task_id, &task);
-        Response::Success(task_id)
+        self.save_task(&task);
+        if let Response::Error(e) = self.apply_overdue_penalty(&task) {
+            return Response::Error(e);
+        }
+        Response::Success(task.id)
+    }
+
+    // Capped by what the owner's deposit has left over its storage cost. Every owner
+    // pays it, since the fee is the incentive for third-party sweeps (see README)
+    fn pay_keeper_fee(&mut self, owner_id: &AccountId, keeper_id: &AccountId, task_count: u64) -> u128 {
+        let mut account = match self.storage_accounts.get(owner_id) {
+            Some(account) => account,
+            None => return 0
+        };
+
+        let fee = self.config.keeper_fee_per_task.0
+            .saturating_mul(task_count as u128)
+            .min(account.available());
+        if fee == 0 {
+            return 0;
+        }
+
+        account.deposit -= fee;
+        self.storage_accounts.insert(owner_id, &account);
+        Promise::new(keeper_id.clone()).transfer(NearToken::from_yoctonear(fee));
+        fee
+    }
+
+    // Points a task earns if completed now: overdue tasks only earn the late
+    // completion share of their owner's penalty policy
+    fn completion_points(&self, task: &Task) -> u32 {
+        match self.penalty_policies.get(task.get_owner_id()) {
+            Some(policy) if task.state == TaskState::Overdue => policy.late_completion_points(task.reward_points),
+            _ => task.reward_points,
+        }
     }
 
-    pub fn delete_task(&mut self, task_id: TaskId) -> TaskActionResponse {
-        let task = match self.tasks.get(&task_id) {
+    fn apply_overdue_penalty(&mut self, task: &Task) -> PointsResponse {
+        let owner_id = task.get_owner_id();
+        let penalty = self.penalty_policies
+            .get(owner_id)
+            .map_or(0, |policy| policy.overdue_penalty(task.reward_points, task.priority));
+
+        // The penalty takes what is left rather than blocking the transition
+        let balance = self.reward_points.get(owner_id).unwrap_or(0);
+        self.debit_points(owner_id, penalty.min(balance), PointsSource::Task(task.id.clone()))
+    }
+
+    pub fn delete_task(&mut self, task_id: TaskId, reparent_subtasks: Option<bool>) -> TaskActionResponse {
+        self.mutate(|contract| contract.internal_delete_task(task_id, reparent_subtasks))
+    }
+
+    fn internal_delete_task(&mut self, task_id: TaskId, reparent_subtasks: Option<bool>) -> TaskActionResponse {
+        let task = match self.load_task(&task_id) {
             Some(t) => t,
             None => return Response::Error(ContractError::NotFound(
                 "Task".to_string(),
@@ -726,19 +1826,51 @@ impl Contract {
         if let Err(e) = task.validate_ownership() {
             return Response::Error(ContractError::AccessError(e));
         }
+
+        let reparent = reparent_subtasks.unwrap_or(false);
+
+        if let Some(ref parent_id) = task.parent_task_id {
+            if let Some(mut parent_task) = self.load_task(parent_id) {
+                parent_task.remove_subtask(&task_id);
+                if reparent {
+                    for subtask_id in &task.subtask_ids {
+                        match parent_task.add_subtask(subtask_id.clone()) {
+                            // The child is already attached
+                            Ok(()) | Err(TaskError::Validation(TaskValidationError::Subtasks {
+                                reason: SubtaskError::DuplicateId, ..
+                            })) => (),
+                            Err(e) => return Response::Error(e.into())
+                        }
+                    }
+                    if let Err(e) = parent_task.validate_storage(&self.config) {
+                        return Response::Error(e.into());
+                    }
+                }
+                self.save_task(&parent_task);
+            }
+        }
     
-        for subtask_id in &task.subtask_ids {
-            if let Some(subtask) = self.tasks.get(subtask_id) {
-                self.tasks.remove(subtask_id);
+        if reparent {
+            for subtask_id in &task.subtask_ids {
+                if let Some(mut subtask) = self.load_task(subtask_id) {
+                    subtask.parent_task_id = task.parent_task_id.clone();
+                    self.save_task(&subtask);
+                }
+            }
+        } else {
+            for subtask in self.collect_descendants(&task) {
+                self.remove_task_record(&subtask.id);
                 self.remove_from_owner_index(
                     subtask.get_owner_id(),
-                    subtask_id,
+                    &subtask.id,
                     IndexType::Task
                 );
+                self.remove_task_dependencies(&subtask.id);
             }
         }
     
-        self.tasks.remove(&task_id);
+        self.remove_task_record(&task_id);
+        self.remove_task_dependencies(&task_id);
         self.remove_from_owner_index(
             task.get_owner_id(),
             &task_id,
@@ -748,87 +1880,376 @@ impl Contract {
         Response::Success(task_id)
     }
     
-    pub fn start_task(&mut self, task_id: TaskId, scheduled_start_time: u64) -> TaskActionResponse {
-        let mut task = match self.tasks.get(&task_id) {
+    pub fn start_task(
+        &mut self,
+        task_id: TaskId,
+        scheduled_start_time: u64,
+        allow_overlap: Option<bool>
+    ) -> TaskActionResponse {
+        self.mutate(|contract| contract.internal_start_task(
+            task_id, scheduled_start_time, allow_overlap
+        ))
+    }
+
+    fn internal_start_task(
+        &mut self,
+        task_id: TaskId,
+        scheduled_start_time: u64,
+        allow_overlap: Option<bool>
+    ) -> TaskActionResponse {
+        let mut task = match self.load_task(&task_id) {
+            Some(t) => t,
+            None => return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("Task {} not found", task_id)
+            ))
+        };
+    
+        if let Err(e) = task.validate_ownership() {
+            return Response::Error(ContractError::AccessError(e));
+        }
+
+        if let Err(e) = self.ensure_unblocked(&task, &HashSet::new()) {
+            return Response::Error(e.into());
+        }
+        
+        let estimated_time_ns = task.estimated_time as u64 * 60 * 1_000_000_000;
+        let scheduled_end_time = scheduled_start_time + estimated_time_ns;
+        
+        task.time_slots.push(TaskTimeSlot {
+            start_time: scheduled_start_time,
+            end_time: scheduled_end_time,
+        });
+        
+        if let Err(e) = task.validate(&self.config) {
+            return Response::Error(e.into());
+        }
+
+        if let Err(e) = self.check_schedule_conflicts(&task, allow_overlap) {
+            return Response::Error(e);
+        }
+        
+        if let Err(e) = task.transition_to(TaskState::InProgress) {
+            return Response::Error(e.into());
+        }
+    
+        self.save_task(&task);
+        Response::Success(task_id)
+    }
+
+    pub fn split_task(
+        &mut self,
+        task_id: TaskId,
+        split_times: Vec<u64>,
+        allow_overlap: Option<bool>
+    ) -> TaskActionResponse {
+        self.mutate(|contract| contract.internal_split_task(
+            task_id, split_times, allow_overlap
+        ))
+    }
+
+    fn internal_split_task(
+        &mut self,
+        task_id: TaskId,
+        split_times: Vec<u64>,
+        allow_overlap: Option<bool>
+    ) -> TaskActionResponse {
+        if split_times.is_empty() || split_times.len() == 1 {
+            return Response::Error(ContractError::ValidationError(
+                "Task".to_string(),
+                "Need at least two split points to split a task".to_string(),
+                None
+            ));
+        }
+    
+        let mut task = match self.load_task(&task_id) {
+            Some(t) => t,
+            None => return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("Task {} not found", task_id)
+            ))
+        };
+    
+        if let Err(e) = task.validate_ownership() {
+            return Response::Error(ContractError::AccessError(e));
+        }
+        
+        // Clear existing time slots
+        task.time_slots.clear();
+        
+        // Ensure the split times are sorted
+        let mut ordered_times = split_times.clone();
+        ordered_times.sort();
+        
+        // Create time slots from split points
+        for i in 0..ordered_times.len() - 1 {
+            task.time_slots.push(TaskTimeSlot {
+                start_time: ordered_times[i],
+                end_time: ordered_times[i + 1],
+            });
+        }
+        
+        if let Err(e) = task.validate(&self.config) {
+            return Response::Error(e.into());
+        }
+
+        if let Err(e) = self.check_schedule_conflicts(&task, allow_overlap) {
+            return Response::Error(e);
+        }
+        
+        if task.state == TaskState::Created {
+            if let Err(e) = task.transition_to(TaskState::InProgress) {
+                return Response::Error(e.into());
+            }
+        }
+        
+        self.save_task(&task);
+        ContractEvent::TaskUpdated {
+            task_id: task_id.clone(),
+            owner_id: task.get_owner_id().clone(),
+        }.emit();
+        Response::Success(task_id)
+    }
+
+    // === Task Trees ===
+    pub fn get_task_tree(&self, task_id: TaskId) -> TaskTreeResponse {
+        let task = match self.load_task(&task_id) {
+            Some(t) => t,
+            None => return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("Task {} not found", task_id)
+            ))
+        };
+
+        Response::Success(self.build_task_tree(task, 0))
+    }
+
+    fn build_task_tree(&self, task: Task, depth: usize) -> TaskTreeNode {
+        let children = if depth >= self.config.max_subtask_depth as usize {
+            Vec::new()
+        } else {
+            task.subtask_ids
+                .iter()
+                .filter_map(|subtask_id| self.load_task(subtask_id))
+                .map(|subtask| self.build_task_tree(subtask, depth + 1))
+                .collect()
+        };
+
+        TaskTreeNode::new(task, children)
+    }
+
+    // Number of ancestors above the task
+    fn task_depth(&self, task: &Task) -> usize {
+        let mut depth = 0;
+        let mut parent_id = task.parent_task_id.clone();
+
+        while let Some(id) = parent_id {
+            depth += 1;
+            if depth > self.config.max_subtask_depth as usize {
+                break;
+            }
+            parent_id = self.load_task(&id).and_then(|parent| parent.parent_task_id);
+        }
+        depth
+    }
+
+    // All tasks below the given one, parents before their children
+    fn collect_descendants(&self, task: &Task) -> Vec<Task> {
+        let mut descendants = Vec::new();
+        let mut queue: VecDeque<TaskId> = task.subtask_ids.iter().cloned().collect();
+
+        while let Some(subtask_id) = queue.pop_front() {
+            if let Some(subtask) = self.load_task(&subtask_id) {
+                queue.extend(subtask.subtask_ids.iter().cloned());
+                descendants.push(subtask);
+            }
+        }
+        descendants
+    }
+
+    // === Task Dependencies ===
+    pub fn add_task_dependency(&mut self, task_id: TaskId, blocker_id: TaskId) -> TaskActionResponse {
+        self.mutate(|contract| contract.internal_add_task_dependency(task_id, blocker_id))
+    }
+
+    fn internal_add_task_dependency(&mut self, task_id: TaskId, blocker_id: TaskId) -> TaskActionResponse {
+        let task = match self.load_task(&task_id) {
+            Some(t) => t,
+            None => return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("Task {} not found", task_id)
+            ))
+        };
+        let blocker = match self.load_task(&blocker_id) {
             Some(t) => t,
             None => return Response::Error(ContractError::NotFound(
                 "Task".to_string(),
-                format!("Task {} not found", task_id)
+                format!("Blocker task {} not found", blocker_id)
             ))
         };
-    
+
         if let Err(e) = task.validate_ownership() {
             return Response::Error(ContractError::AccessError(e));
         }
-        
-        let estimated_time_ns = task.estimated_time as u64 * 60 * 1_000_000_000;
-        let scheduled_end_time = scheduled_start_time + estimated_time_ns;
-        
-        task.time_slots.push(TaskTimeSlot {
-            start_time: scheduled_start_time,
-            end_time: scheduled_end_time,
-        });
-        
-        if let Err(e) = task.validate() {
-            return Response::Error(e.into());
+        if let Err(e) = blocker.validate_ownership() {
+            return Response::Error(ContractError::AccessError(e));
         }
-        
-        if let Err(e) = task.transition_to(TaskState::InProgress) {
-            return Response::Error(e.into());
+
+        let mut blockers = self.task_blockers.get(&task_id).unwrap_or_default();
+        if blockers.contains(&blocker_id) {
+            return Response::Error(TaskValidationError::Dependencies {
+                reason: SubtaskError::DuplicateId,
+                blocker_id,
+            }.into());
         }
-    
-        self.tasks.insert(&task_id, &task);
+
+        // Adding the edge closes a cycle if the blocker already waits on the task
+        if dependency::has_path(&blocker_id, &task_id, |id| self.task_blockers.get(id).unwrap_or_default()) {
+            return Response::Error(TaskValidationError::Dependencies {
+                reason: SubtaskError::CircularDependency,
+                blocker_id,
+            }.into());
+        }
+
+        blockers.push(blocker_id.clone());
+        self.task_blockers.insert(&task_id, &blockers);
+
+        let mut dependents = self.task_dependents.get(&blocker_id).unwrap_or_default();
+        dependents.push(task_id.clone());
+        self.task_dependents.insert(&blocker_id, &dependents);
+
+        ContractEvent::TaskDependencyAdded {
+            task_id: task_id.clone(),
+            blocker_id,
+            owner_id: task.get_owner_id().clone(),
+        }.emit();
         Response::Success(task_id)
     }
 
-    pub fn split_task(&mut self, task_id: TaskId, split_times: Vec<u64>) -> TaskActionResponse {
-        if split_times.is_empty() || split_times.len() == 1 {
-            return Response::Error(ContractError::ValidationError(
-                "Task".to_string(),
-                "Need at least two split points to split a task".to_string(),
-                None
-            ));
-        }
-    
-        let mut task = match self.tasks.get(&task_id) {
+    pub fn remove_task_dependency(&mut self, task_id: TaskId, blocker_id: TaskId) -> TaskActionResponse {
+        self.mutate(|contract| contract.internal_remove_task_dependency(
+            task_id, blocker_id
+        ))
+    }
+
+    fn internal_remove_task_dependency(&mut self, task_id: TaskId, blocker_id: TaskId) -> TaskActionResponse {
+        let task = match self.load_task(&task_id) {
             Some(t) => t,
             None => return Response::Error(ContractError::NotFound(
                 "Task".to_string(),
                 format!("Task {} not found", task_id)
             ))
         };
-    
+
         if let Err(e) = task.validate_ownership() {
             return Response::Error(ContractError::AccessError(e));
         }
-        
-        // Clear existing time slots
-        task.time_slots.clear();
-        
-        // Ensure the split times are sorted
-        let mut ordered_times = split_times.clone();
-        ordered_times.sort();
-        
-        // Create time slots from split points
-        for i in 0..ordered_times.len() - 1 {
-            task.time_slots.push(TaskTimeSlot {
-                start_time: ordered_times[i],
-                end_time: ordered_times[i + 1],
-            });
+
+        let mut blockers = self.task_blockers.get(&task_id).unwrap_or_default();
+        if !blockers.contains(&blocker_id) {
+            return Response::Error(ContractError::NotFound(
+                "Dependency".to_string(),
+                format!("Task {} is not blocked by {}", task_id, blocker_id)
+            ));
         }
-        
-        if let Err(e) = task.validate() {
-            return Response::Error(e.into());
+
+        blockers.retain(|id| id != &blocker_id);
+        self.set_task_blockers(&task_id, blockers);
+
+        let mut dependents = self.task_dependents.get(&blocker_id).unwrap_or_default();
+        dependents.retain(|id| id != &task_id);
+        self.set_task_dependents(&blocker_id, dependents);
+
+        ContractEvent::TaskDependencyRemoved {
+            task_id: task_id.clone(),
+            blocker_id,
+            owner_id: task.get_owner_id().clone(),
+        }.emit();
+        Response::Success(task_id)
+    }
+
+    pub fn get_task_blockers(&self, task_id: TaskId) -> Response<Vec<TaskId>, ContractError> {
+        if self.load_task(&task_id).is_none() {
+            return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("Task {} not found", task_id)
+            ));
         }
-        
-        if task.state == TaskState::Created {
-            if let Err(e) = task.transition_to(TaskState::InProgress) {
-                return Response::Error(e.into());
-            }
+
+        Response::Success(self.task_blockers.get(&task_id).unwrap_or_default())
+    }
+
+    // Empty rather than NotFound when the owner has no open tasks
+    pub fn get_task_order(&self, owner_id: AccountId) -> TaskListResponse {
+        let incomplete_tasks: Vec<Task> = self.tasks_per_owner
+            .get(&owner_id)
+            .map(|task_set| task_set
+                .iter()
+                .filter_map(|task_id| self.load_task(&task_id))
+                .filter(|task| task.state != TaskState::Completed)
+                .collect())
+            .unwrap_or_default();
+
+        let blockers: HashMap<TaskId, Vec<TaskId>> = incomplete_tasks
+            .iter()
+            .filter_map(|task| self.task_blockers.get(&task.id).map(|list| (task.id.clone(), list)))
+            .collect();
+
+        Response::Success(dependency::topological_order(incomplete_tasks, &blockers))
+    }
+
+    // Blockers listed in `completing` are about to complete in the same call
+    fn ensure_unblocked(&self, task: &Task, completing: &HashSet<TaskId>) -> Result<(), TaskStateError> {
+        let blocker_ids: Vec<TaskId> = self.task_blockers
+            .get(&task.id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|blocker_id| !completing.contains(blocker_id))
+            .filter(|blocker_id| self.load_task(blocker_id).is_some_and(|blocker| !self.is_satisfied(&blocker)))
+            .collect();
+
+        if blocker_ids.is_empty() {
+            Ok(())
+        } else {
+            Err(TaskStateError::Blocked { state: task.state, blocker_ids })
+        }
+    }
+
+    // A recurring task reopens as soon as it is completed, so any recorded
+    // completion releases the tasks waiting on it
+    fn is_satisfied(&self, blocker: &Task) -> bool {
+        blocker.state == TaskState::Completed
+            || self.task_completions.get(&blocker.id).is_some_and(|completions| !completions.is_empty())
+    }
+
+    fn remove_task_dependencies(&mut self, task_id: &TaskId) {
+        for blocker_id in self.task_blockers.remove(task_id).unwrap_or_default() {
+            let mut dependents = self.task_dependents.get(&blocker_id).unwrap_or_default();
+            dependents.retain(|id| id != task_id);
+            self.set_task_dependents(&blocker_id, dependents);
+        }
+
+        for dependent_id in self.task_dependents.remove(task_id).unwrap_or_default() {
+            let mut blockers = self.task_blockers.get(&dependent_id).unwrap_or_default();
+            blockers.retain(|id| id != task_id);
+            self.set_task_blockers(&dependent_id, blockers);
+        }
+    }
+
+    fn set_task_blockers(&mut self, task_id: &TaskId, blockers: Vec<TaskId>) {
+        if blockers.is_empty() {
+            self.task_blockers.remove(task_id);
+        } else {
+            self.task_blockers.insert(task_id, &blockers);
+        }
+    }
+
+    fn set_task_dependents(&mut self, task_id: &TaskId, dependents: Vec<TaskId>) {
+        if dependents.is_empty() {
+            self.task_dependents.remove(task_id);
+        } else {
+            self.task_dependents.insert(task_id, &dependents);
         }
-        
-        self.tasks.insert(&task_id, &task);
-        Response::Success(task_id)
     }
 
     // === Habit Management ===
@@ -843,7 +2264,7 @@ impl Contract {
         
         let habits: Vec<Habit> = habit_set
             .iter()
-            .filter_map(|habit_id| self.habits.get(&habit_id))
+            .filter_map(|habit_id| self.load_habit(&habit_id))
             .collect();
         
         if habits.is_empty() {
@@ -856,8 +2277,246 @@ impl Contract {
         Response::Success(habits)
     }
     
+    pub fn add_habit(&mut self, task_id: TaskId, recurrence: RecurrencePattern) -> HabitActionResponse {
+        self.mutate(|contract| contract.internal_add_habit(task_id, recurrence))
+    }
+
+    fn internal_add_habit(&mut self, task_id: TaskId, recurrence: RecurrencePattern) -> HabitActionResponse {
+        let task = match self.load_task(&task_id) {
+            Some(t) => t,
+            None => return Response::Error(ContractError::NotFound(
+                "Task".to_string(),
+                format!("Task {} not found", task_id)
+            ))
+        };
+
+        if let Err(e) = task.validate_ownership() {
+            return Response::Error(ContractError::AccessError(e));
+        }
+
+        if task.state == TaskState::Completed {
+            return Response::Error(ContractError::StateError(
+                "Task".to_string(),
+                format!("{:?}", task.state),
+                "attach habit".to_string(),
+                "Completed tasks cannot recur".to_string()
+            ));
+        }
+
+        if let Some(habit_id) = self.habit_by_task.get(&task_id) {
+            return Response::Error(ContractError::AlreadyExists("Habit".to_string(), habit_id));
+        }
+
+        let owner_id = task.get_owner_id().clone();
+        let habit_id = self.next_id("habit", &owner_id);
+        if self.load_habit(&habit_id).is_some() {
+            return Response::Error(ContractError::AlreadyExists("Habit".to_string(), habit_id));
+        }
+
+        match Habit::new(habit_id, task_id, recurrence, owner_id, &self.config) {
+            Ok(habit) => {
+                let habit_id = habit.id.clone();
+                let utc_offset_minutes = self.utc_offset(habit.get_owner_id());
+                self.align_habit_deadline(&habit, env::block_timestamp(), utc_offset_minutes);
+                self.insert_new_habit(habit);
+                Response::Success(habit_id)
+            },
+            Err(e) => Response::Error(e.into())
+        }
+    }
+
+    pub fn update_habit(&mut self, habit_id: HabitId, recurrence: RecurrencePattern) -> HabitActionResponse {
+        self.mutate(|contract| contract.internal_update_habit(habit_id, recurrence))
+    }
+
+    fn internal_update_habit(&mut self, habit_id: HabitId, recurrence: RecurrencePattern) -> HabitActionResponse {
+        let mut habit = match self.load_owned_habit(&habit_id) {
+            Ok(h) => h,
+            Err(e) => return Response::Error(e)
+        };
+
+        let current_time = env::block_timestamp();
+        habit.recurrence = recurrence.anchored(current_time);
+        if let Err(e) = habit.validate(&self.config) {
+            return Response::Error(e.into());
+        }
+        let utc_offset_minutes = self.utc_offset(habit.get_owner_id());
+        habit.rebase(current_time, utc_offset_minutes);
+        self.align_habit_deadline(&habit, current_time, utc_offset_minutes);
+
+        let owner_id = habit.get_owner_id().clone();
+        self.habits.insert(&habit_id, &habit.into());
+        ContractEvent::HabitUpdated {
+            habit_id: habit_id.clone(),
+            owner_id,
+        }.emit();
+        Response::Success(habit_id)
+    }
+
+    pub fn pause_habit(&mut self, habit_id: HabitId) -> HabitActionResponse {
+        self.mutate(|contract| contract.internal_pause_habit(habit_id))
+    }
+
+    fn internal_pause_habit(&mut self, habit_id: HabitId) -> HabitActionResponse {
+        let mut habit = match self.load_owned_habit(&habit_id) {
+            Ok(h) => h,
+            Err(e) => return Response::Error(e)
+        };
+
+        if let Err(e) = habit.pause() {
+            return Response::Error(HabitError::from(e).into());
+        }
+
+        let owner_id = habit.get_owner_id().clone();
+        self.habits.insert(&habit_id, &habit.into());
+        ContractEvent::HabitPaused {
+            habit_id: habit_id.clone(),
+            owner_id,
+        }.emit();
+        Response::Success(habit_id)
+    }
+
+    // Resuming reopens a task that was completed or went overdue during the pause
+    pub fn resume_habit(&mut self, habit_id: HabitId) -> HabitActionResponse {
+        self.mutate(|contract| contract.internal_resume_habit(habit_id))
+    }
+
+    fn internal_resume_habit(&mut self, habit_id: HabitId) -> HabitActionResponse {
+        let mut habit = match self.load_owned_habit(&habit_id) {
+            Ok(h) => h,
+            Err(e) => return Response::Error(e)
+        };
+
+        let current_time = env::block_timestamp();
+        let utc_offset_minutes = self.utc_offset(habit.get_owner_id());
+        if let Err(e) = habit.resume(current_time, utc_offset_minutes) {
+            return Response::Error(HabitError::from(e).into());
+        }
+
+        if let Some(mut task) = self.load_task(&habit.task_id) {
+            let next_deadline = habit.next_deadline(current_time, utc_offset_minutes);
+            if let (TaskState::Completed | TaskState::Overdue, Some(deadline)) = (task.state, next_deadline) {
+                task.state = TaskState::Created;
+                task.deadline = deadline;
+                task.time_slots.clear();
+                self.save_task(&task);
+            }
+        }
+
+        let owner_id = habit.get_owner_id().clone();
+        self.habits.insert(&habit_id, &habit.into());
+        ContractEvent::HabitResumed {
+            habit_id: habit_id.clone(),
+            owner_id,
+        }.emit();
+        Response::Success(habit_id)
+    }
+
+    // The backing task stays as a one-off task
+    pub fn delete_habit(&mut self, habit_id: HabitId) -> HabitActionResponse {
+        self.mutate(|contract| contract.internal_delete_habit(habit_id))
+    }
+
+    fn internal_delete_habit(&mut self, habit_id: HabitId) -> HabitActionResponse {
+        let habit = match self.load_owned_habit(&habit_id) {
+            Ok(h) => h,
+            Err(e) => return Response::Error(e)
+        };
+
+        self.remove_habit_record(&habit);
+        Response::Success(habit_id)
+    }
+
+    fn load_owned_habit(&self, habit_id: &HabitId) -> Result<Habit, ContractError> {
+        let habit = self.load_habit(habit_id).ok_or_else(|| ContractError::NotFound(
+            "Habit".to_string(),
+            format!("Habit {} not found", habit_id)
+        ))?;
+        habit.validate_ownership()?;
+        Ok(habit)
+    }
+
+    // An open task moves to the next occurrence of its habit's recurrence, keeping
+    // only the time slots that still end before it
+    fn align_habit_deadline(&mut self, habit: &Habit, current_time: u64, utc_offset_minutes: i32) {
+        if let Some(mut task) = self.load_task(&habit.task_id) {
+            let next_deadline = habit.next_deadline(current_time, utc_offset_minutes);
+            if let (TaskState::Created | TaskState::InProgress, Some(deadline)) = (task.state, next_deadline) {
+                task.deadline = deadline;
+                task.time_slots.retain(|slot| slot.end_time < deadline);
+                self.save_task(&task);
+            }
+        }
+    }
+
+    fn insert_new_habit(&mut self, habit: Habit) {
+        let habit_id = habit.id.clone();
+        let task_id = habit.task_id.clone();
+        let owner_id = habit.get_owner_id().clone();
+
+        self.habits.insert(&habit_id, &habit.into());
+        self.habit_by_task.insert(&task_id, &habit_id);
+        self.add_to_owner_index(&owner_id, &habit_id, IndexType::Habit);
+        ContractEvent::HabitCreated {
+            habit_id,
+            task_id,
+            owner_id,
+        }.emit();
+    }
+
+    fn remove_habit_record(&mut self, habit: &Habit) {
+        self.habits.remove(&habit.id);
+        self.habit_by_task.remove(&habit.task_id);
+        self.remove_from_owner_index(habit.get_owner_id(), &habit.id, IndexType::Habit);
+        if let Some(mut history) = self.habit_history.remove(&habit.id) {
+            history.clear();
+        }
+        ContractEvent::HabitDeleted {
+            habit_id: habit.id.clone(),
+            task_id: habit.task_id.clone(),
+            owner_id: habit.get_owner_id().clone(),
+        }.emit();
+    }
+
+    fn append_habit_history(&mut self, habit_id: &HabitId, entries: &[HabitHistoryEntry]) {
+        if entries.is_empty() {
+            return;
+        }
+
+        let mut history = self.habit_history
+            .get(habit_id)
+            .unwrap_or_else(|| Vector::new(habit_history_prefix(habit_id)));
+        for entry in entries {
+            history.push(entry);
+        }
+        self.habit_history.insert(habit_id, &history);
+    }
+
+    fn use_streak_freezes(&mut self, habit: &Habit, entries: &[HabitHistoryEntry], available_freezes: u32) {
+        let occurrence_days: Vec<u64> = entries.iter()
+            .filter(|entry| entry.kind == HabitHistoryKind::FreezeUsed)
+            .map(|entry| entry.occurrence_day)
+            .collect();
+        if occurrence_days.is_empty() {
+            return;
+        }
+
+        let remaining = available_freezes.saturating_sub(occurrence_days.len() as u32);
+        if remaining == 0 {
+            self.streak_freezes.remove(habit.get_owner_id());
+        } else {
+            self.streak_freezes.insert(habit.get_owner_id(), &remaining);
+        }
+        ContractEvent::StreakFreezesUsed {
+            habit_id: habit.id.clone(),
+            owner_id: habit.get_owner_id().clone(),
+            occurrence_days,
+            remaining,
+        }.emit();
+    }
+
     pub fn get_habit_streak(&self, habit_id: HabitId) -> Response<u32, ContractError> {
-        let habit = match self.habits.get(&habit_id) {
+        let habit = match self.load_habit(&habit_id) {
             Some(h) => h,
             None => return Response::Error(ContractError::NotFound(
                 "Habit".to_string(),
@@ -871,9 +2530,154 @@ impl Contract {
         
         Response::Success(habit.streak)
     }
+
+    // Streaks, completion rate over the last `periods` occurrences and a per-day
+    // completion map for the local days covered by [start_time, end_time)
+    pub fn get_habit_stats(
+        &self,
+        habit_id: HabitId,
+        periods: u32,
+        start_time: u64,
+        end_time: u64
+    ) -> HabitStatsResponse {
+        let habit = match self.load_habit(&habit_id) {
+            Some(h) => h,
+            None => return Response::Error(ContractError::NotFound(
+                "Habit".to_string(),
+                format!("Habit {} not found", habit_id)
+            ))
+        };
+
+        if let Err(e) = habit.validate_ownership() {
+            return Response::Error(ContractError::AccessError(e));
+        }
+
+        if periods == 0 || periods > MAX_STATS_PERIODS {
+            return Response::Error(ContractError::ValidationError(
+                "Habit".to_string(),
+                format!("Periods must be between 1 and {}", MAX_STATS_PERIODS),
+                Some(format!("periods: {}", periods))
+            ));
+        }
+
+        let utc_offset_minutes = self.utc_offset(habit.get_owner_id());
+        let from_day = day_index(start_time, utc_offset_minutes);
+        let to_day = day_index(end_time.saturating_sub(1), utc_offset_minutes);
+        if end_time <= start_time || to_day - from_day >= MAX_STATS_DAYS {
+            return Response::Error(ContractError::ValidationError(
+                "Habit".to_string(),
+                format!("Range must be non-empty and cover at most {} days", MAX_STATS_DAYS),
+                Some(format!("start: {}, end: {}", start_time, end_time))
+            ));
+        }
+
+        let completions = self.task_completions.get(&habit.task_id).unwrap_or_default();
+        Response::Success(habit.stats(
+            &completions,
+            periods,
+            from_day,
+            to_day,
+            utc_offset_minutes
+        ))
+    }
     
+    pub fn get_habit_history(
+        &self,
+        habit_id: HabitId,
+        from_index: Option<u64>,
+        limit: Option<u64>
+    ) -> HabitHistoryResponse {
+        let habit = match self.load_habit(&habit_id) {
+            Some(h) => h,
+            None => return Response::Error(ContractError::NotFound(
+                "Habit".to_string(),
+                format!("Habit {} not found", habit_id)
+            ))
+        };
+
+        if let Err(e) = habit.validate_ownership() {
+            return Response::Error(ContractError::AccessError(e));
+        }
+
+        let history = match self.habit_history.get(&habit_id) {
+            Some(history) => history,
+            None => return Response::Success(HabitHistoryPage { entries: Vec::new(), total: 0 })
+        };
+
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(self.config.default_page_limit).min(self.config.max_page_limit);
+        let entries = (from_index..history.len().min(from_index.saturating_add(limit)))
+            .filter_map(|index| history.get(index))
+            .collect();
+
+        Response::Success(HabitHistoryPage { entries, total: history.len() })
+    }
+
+    // Completions up to this many minutes after an occurrence's day still count for it
+    pub fn set_habit_grace(&mut self, habit_id: HabitId, grace_minutes: u32) -> HabitActionResponse {
+        self.mutate(|contract| contract.internal_set_habit_grace(habit_id, grace_minutes))
+    }
+
+    fn internal_set_habit_grace(&mut self, habit_id: HabitId, grace_minutes: u32) -> HabitActionResponse {
+        let mut habit = match self.load_owned_habit(&habit_id) {
+            Ok(h) => h,
+            Err(e) => return Response::Error(e)
+        };
+
+        habit.grace_minutes = grace_minutes;
+        if let Err(e) = habit.validate_grace() {
+            return Response::Error(HabitError::from(e).into());
+        }
+
+        let owner_id = habit.get_owner_id().clone();
+        self.habits.insert(&habit_id, &habit.into());
+        ContractEvent::HabitUpdated {
+            habit_id: habit_id.clone(),
+            owner_id,
+        }.emit();
+        Response::Success(habit_id)
+    }
+
+    pub fn get_streak_freezes(&self, account_id: AccountId) -> Response<u32, ContractError> {
+        Response::Success(self.streak_freezes.get(&account_id).unwrap_or(0))
+    }
+
+    // Freezes are spent automatically, one per missed occurrence, when a habit
+    // completion would otherwise reset its streak
+    pub fn buy_streak_freezes(&mut self, quantity: u32) -> Response<u32, ContractError> {
+        self.mutate(|contract| contract.internal_buy_streak_freezes(quantity))
+    }
+
+    fn internal_buy_streak_freezes(&mut self, quantity: u32) -> Response<u32, ContractError> {
+        let account_id = env::predecessor_account_id();
+        let held = self.streak_freezes.get(&account_id).unwrap_or(0);
+
+        if quantity == 0 || held.saturating_add(quantity) > self.config.max_streak_freezes {
+            return Response::Error(ContractError::ValidationError(
+                "StreakFreeze".to_string(),
+                format!("Quantity must be positive and at most {} freezes can be held", self.config.max_streak_freezes),
+                Some(format!("held: {}, quantity: {}", held, quantity))
+            ));
+        }
+
+        let cost = quantity.saturating_mul(self.config.streak_freeze_cost);
+        if let Response::Error(e) = self.debit_points(&account_id, cost, PointsSource::StreakFreeze) {
+            return Response::Error(e);
+        }
+
+        let balance = held + quantity;
+        self.streak_freezes.insert(&account_id, &balance);
+        ContractEvent::StreakFreezesPurchased {
+            account_id,
+            quantity,
+            cost,
+            balance,
+        }.emit();
+        Response::Success(balance)
+    }
+
     pub fn get_task_completion_history(&self, task_id: TaskId) -> Response<Vec<u64>, ContractError> {
-        let task = match self.tasks.get(&task_id) {
+        let task = match self.load_task(&task_id) {
             Some(t) => t,
             None => return Response::Error(ContractError::NotFound(
                 "Task".to_string(),
@@ -901,7 +2705,7 @@ impl Contract {
     
         let rewards: Vec<Reward> = reward_set
             .iter()
-            .filter_map(|reward_id| self.rewards.get(&reward_id))
+            .filter_map(|reward_id| self.load_reward(&reward_id))
             .filter(|reward| reward.state == RewardState::Active)
             .collect();
     
@@ -926,7 +2730,7 @@ impl Contract {
     
         let rewards: Vec<Reward> = reward_set
             .iter()
-            .filter_map(|reward_id| self.rewards.get(&reward_id))
+            .filter_map(|reward_id| self.load_reward(&reward_id))
             .filter(|reward| reward.state == RewardState::Completed)
             .collect();
     
@@ -941,22 +2745,43 @@ impl Contract {
     }
 
     pub fn add_reward(&mut self, title: String, description: String, cost: u32) -> RewardActionResponse {
+        self.mutate(|contract| contract.internal_add_reward(title, description, cost))
+    }
+
+    fn internal_add_reward(&mut self, title: String, description: String, cost: u32) -> RewardActionResponse {
         let owner_id = env::predecessor_account_id();
     
-        let reward = match Reward::new(title, description, cost, owner_id.clone()) {
+        let reward_id = self.next_id("reward", &owner_id);
+        if self.load_reward(&reward_id).is_some() {
+            return Response::Error(ContractError::AlreadyExists("Reward".to_string(), reward_id));
+        }
+    
+        let reward = match Reward::new(reward_id, title, description, cost, owner_id.clone(), &self.config) {
             Ok(r) => r,
             Err(e) => return Response::Error(e.into())
         };
     
         let reward_id = reward.id.clone();
-        self.rewards.insert(&reward_id, &reward);
+        let cost = reward.cost;
+        self.rewards.insert(&reward_id, &reward.into());
         self.add_to_owner_index(&owner_id, &reward_id, IndexType::Reward);
     
+        ContractEvent::RewardCreated {
+            reward_id: reward_id.clone(),
+            owner_id,
+            cost,
+        }.emit();
         Response::Success(reward_id)
     }
     
     pub fn update_reward(&mut self, reward_id: RewardId, title: String, description: String, cost: u32) -> RewardActionResponse {
-        let mut reward = match self.rewards.get(&reward_id) {
+        self.mutate(|contract| contract.internal_update_reward(
+            reward_id, title, description, cost
+        ))
+    }
+
+    fn internal_update_reward(&mut self, reward_id: RewardId, title: String, description: String, cost: u32) -> RewardActionResponse {
+        let mut reward = match self.load_reward(&reward_id) {
             Some(r) => r,
             None => return Response::Error(ContractError::NotFound(
                 "Reward".to_string(),
@@ -972,16 +2797,25 @@ impl Contract {
         reward.description = description;
         reward.cost = cost;
     
-        if let Err(e) = reward.validate() {
+        if let Err(e) = reward.validate(&self.config) {
             return Response::Error(e.into());
         }
     
-        self.rewards.insert(&reward_id, &reward);
+        ContractEvent::RewardUpdated {
+            reward_id: reward_id.clone(),
+            owner_id: reward.get_owner_id().clone(),
+            cost: reward.cost,
+        }.emit();
+        self.rewards.insert(&reward_id, &reward.into());
         Response::Success(reward_id)
     }
     
     pub fn delete_reward(&mut self, reward_id: RewardId) -> RewardActionResponse {
-        let reward = match self.rewards.get(&reward_id) {
+        self.mutate(|contract| contract.internal_delete_reward(reward_id))
+    }
+
+    fn internal_delete_reward(&mut self, reward_id: RewardId) -> RewardActionResponse {
+        let reward = match self.load_reward(&reward_id) {
             Some(r) => r,
             None => return Response::Error(ContractError::NotFound(
                 "Reward".to_string(),
@@ -996,11 +2830,19 @@ impl Contract {
         self.rewards.remove(&reward_id);
         self.remove_from_owner_index(reward.get_owner_id(), &reward_id, IndexType::Reward);
     
+        ContractEvent::RewardDeleted {
+            reward_id: reward_id.clone(),
+            owner_id: reward.get_owner_id().clone(),
+        }.emit();
         Response::Success(reward_id)
     }
     
     pub fn redeem_reward(&mut self, reward_id: RewardId) -> RewardActionResponse {
-        let reward = match self.rewards.get(&reward_id) {
+        self.mutate(|contract| contract.internal_redeem_reward(reward_id))
+    }
+
+    fn internal_redeem_reward(&mut self, reward_id: RewardId) -> RewardActionResponse {
+        let reward = match self.load_reward(&reward_id) {
             Some(r) => r,
             None => return Response::Error(ContractError::NotFound(
                 "Reward".to_string(),
@@ -1012,29 +2854,25 @@ impl Contract {
             return Response::Error(ContractError::AccessError(e));
         }
     
-        let available_points = match self.get_reward_points(reward.get_owner_id()) {
-            Response::Success(points) => points,
-            Response::Error(e) => return Response::Error(e)
-        };
-    
-        if available_points < reward.cost {
-            return Response::Error(ContractError::StateError(
-                "Reward".to_string(),
-                format!("available: {}", available_points),
-                format!("required: {}", reward.cost),
-                "Insufficient points for redemption".to_string()
-            ));
-        }
-    
-        let new_points = available_points - reward.cost;
-        self.reward_points.insert(reward.get_owner_id(), &new_points);
-    
         let mut updated_reward = reward.clone();
         if let Err(e) = updated_reward.transition_to(RewardState::Completed) {
             return Response::Error(e.into());
         }
     
-        self.rewards.insert(&reward_id, &updated_reward);
+        if let Response::Error(e) = self.debit_points(
+            reward.get_owner_id(),
+            reward.cost,
+            PointsSource::Reward(reward_id.clone())
+        ) {
+            return Response::Error(e);
+        }
+    
+        self.rewards.insert(&reward_id, &updated_reward.into());
+        ContractEvent::RewardRedeemed {
+            reward_id: reward_id.clone(),
+            owner_id: reward.get_owner_id().clone(),
+            cost: reward.cost,
+        }.emit();
         Response::Success(reward_id)
     }
     
@@ -1050,7 +2888,7 @@ impl Contract {
     
         let slots: Vec<TimeSlot> = slot_set
             .iter()
-            .filter_map(|slot_id| self.time_slots.get(&slot_id))
+            .filter_map(|slot_id| self.load_time_slot(&slot_id))
             .collect();
     
         if slots.is_empty() {
@@ -1080,11 +2918,10 @@ impl Contract {
     
         let slots: Vec<TimeSlot> = slot_set
             .iter()
-            .filter_map(|slot_id| self.time_slots.get(&slot_id))
+            .filter_map(|slot_id| self.load_time_slot(&slot_id))
             .filter(|slot| {
-                slot.start_minutes < end_minutes && 
-                slot.end_minutes > start_minutes &&
-                slot_type.as_ref().map_or(true, |t| slot.slot_type == *t)
+                slot.covers_minutes(start_minutes, end_minutes) &&
+                slot_type.as_ref().is_none_or(|t| slot.slot_type == *t)
             })
             .collect();
     
@@ -1098,20 +2935,57 @@ impl Contract {
         Response::Success(slots)
     }
     
+    pub fn get_time_slot_occurrences(
+        &self,
+        owner_id: AccountId,
+        start_time: u64,
+        end_time: u64,
+        slot_type: Option<SlotType>
+    ) -> SlotOccurrenceListResponse {
+        if end_time <= start_time || end_time - start_time > self.config.max_slot_future_time {
+            return Response::Error(ContractError::ValidationError(
+                "TimeSlot".to_string(),
+                "Range must be non-empty and no longer than the slot planning window".to_string(),
+                Some(format!("start: {}, end: {}", start_time, end_time))
+            ));
+        }
+
+        Response::Success(self.owner_slot_occurrences(&owner_id, start_time, end_time, slot_type))
+    }
+    
     pub fn add_time_slot(
         &mut self,
         start_minutes: u32,
         end_minutes: u32,
         slot_type: SlotType,
         recurrence: RecurrencePattern,
+    ) -> TimeSlotActionResponse {
+        self.mutate(|contract| contract.internal_add_time_slot(
+            start_minutes, end_minutes, slot_type, recurrence
+        ))
+    }
+
+    fn internal_add_time_slot(
+        &mut self,
+        start_minutes: u32,
+        end_minutes: u32,
+        slot_type: SlotType,
+        recurrence: RecurrencePattern,
     ) -> TimeSlotActionResponse {
         let owner_id = env::predecessor_account_id();
     
+        let slot_id = self.next_id("time_slot", &owner_id);
+        if self.load_time_slot(&slot_id).is_some() {
+            return Response::Error(ContractError::AlreadyExists("TimeSlot".to_string(), slot_id));
+        }
+    
         let mut time_slot = match TimeSlot::new(
+            slot_id,
             start_minutes,
             end_minutes,
             recurrence,
-            owner_id.clone()
+            owner_id.clone(),
+            &self.config
         ) {
             Ok(t) => t,
             Err(e) => return Response::Error(e.into())
@@ -1120,26 +2994,18 @@ impl Contract {
         time_slot.slot_type = slot_type;
         let slot_id = time_slot.id.clone();
     
-        match self.get_time_slots_by_timeframe(
-            owner_id.clone(),
-            start_minutes,
-            end_minutes,
-            Some(slot_type)
-        ) {
-            Response::Success(slots) => {
-                for existing_slot in slots {
-                    if existing_slot.id != slot_id && existing_slot.overlaps_with(&time_slot) {
-                        return Response::Error(ContractError::Operation(
-                            format!("Time slot overlaps with existing slot {}", existing_slot.id)
-                        ));
-                    }
-                }
-            },
-            _ => {}
+        if let Some(existing_id) = self.find_slot_overlap(&time_slot) {
+            return Response::Error(ContractError::Operation(
+                format!("Time slot overlaps with existing slot {}", existing_id)
+            ));
         }
     
-        self.time_slots.insert(&slot_id, &time_slot);
+        self.time_slots.insert(&slot_id, &time_slot.into());
         self.add_to_owner_index(&owner_id, &slot_id, IndexType::TimeSlot);
+        ContractEvent::TimeSlotCreated {
+            slot_id: slot_id.clone(),
+            owner_id,
+        }.emit();
         Response::Success(slot_id)
     }
     
@@ -1150,7 +3016,19 @@ impl Contract {
         end_minutes: u32,
         recurrence: RecurrencePattern,
     ) -> TimeSlotActionResponse {
-        let mut existing_slot = match self.time_slots.get(&slot_id) {
+        self.mutate(|contract| contract.internal_update_time_slot(
+            slot_id, start_minutes, end_minutes, recurrence
+        ))
+    }
+
+    fn internal_update_time_slot(
+        &mut self,
+        slot_id: TimeSlotId,
+        start_minutes: u32,
+        end_minutes: u32,
+        recurrence: RecurrencePattern,
+    ) -> TimeSlotActionResponse {
+        let mut existing_slot = match self.load_time_slot(&slot_id) {
             Some(s) => s,
             None => return Response::Error(ContractError::NotFound(
                 "TimeSlot".to_string(),
@@ -1165,38 +3043,32 @@ impl Contract {
         existing_slot.start_minutes = start_minutes;
         existing_slot.end_minutes = end_minutes;
         existing_slot.duration = Some((end_minutes + 1440 - start_minutes) % 1440);
-        existing_slot.recurrence = recurrence;
+        existing_slot.recurrence = recurrence.anchored(env::block_timestamp());
     
-        if let Err(e) = existing_slot.validate() {
+        if let Err(e) = existing_slot.validate(&self.config) {
             return Response::Error(e.into());
         }
     
-        match self.get_time_slots_by_timeframe(
-            existing_slot.get_owner_id().clone(),
-            start_minutes,
-            end_minutes,
-            Some(existing_slot.slot_type)
-        ) {
-            Response::Success(slots) => {
-                for slot in slots {
-                    if slot.id != slot_id && slot.overlaps_with(&existing_slot) {
-                        return Response::Error(ContractError::Operation(
-                            format!("Would overlap with existing time slot {}", slot.id)
-                        ));
-                    }
-                }
-                self.time_slots.insert(&slot_id, &existing_slot);
-                Response::Success(slot_id)
-            },
-            _ => {
-                self.time_slots.insert(&slot_id, &existing_slot);
-                Response::Success(slot_id)
-            }
+        if let Some(other_id) = self.find_slot_overlap(&existing_slot) {
+            return Response::Error(ContractError::Operation(
+                format!("Would overlap with existing time slot {}", other_id)
+            ));
         }
+
+        ContractEvent::TimeSlotUpdated {
+            slot_id: slot_id.clone(),
+            owner_id: existing_slot.get_owner_id().clone(),
+        }.emit();
+        self.time_slots.insert(&slot_id, &existing_slot.into());
+        Response::Success(slot_id)
     }
     
     pub fn delete_time_slot(&mut self, slot_id: TimeSlotId) -> TimeSlotActionResponse {
-        let slot = match self.time_slots.get(&slot_id) {
+        self.mutate(|contract| contract.internal_delete_time_slot(slot_id))
+    }
+
+    fn internal_delete_time_slot(&mut self, slot_id: TimeSlotId) -> TimeSlotActionResponse {
+        let slot = match self.load_time_slot(&slot_id) {
             Some(s) => s,
             None => return Response::Error(ContractError::NotFound(
                 "TimeSlot".to_string(),
@@ -1210,11 +3082,764 @@ impl Contract {
     
         self.time_slots.remove(&slot_id);
         self.remove_from_owner_index(
-            &slot.get_owner_id(),
+            slot.get_owner_id(),
             &slot_id,
             IndexType::TimeSlot
         );
         
+        ContractEvent::TimeSlotDeleted {
+            slot_id: slot_id.clone(),
+            owner_id: slot.get_owner_id().clone(),
+        }.emit();
         Response::Success(slot_id)
     }
-}
\ No newline at end of file
+
+    // Slots of the same type may not share any time. Their occurrences are compared
+    // over the task planning horizon, so rules on different days never clash
+    fn find_slot_overlap(&self, slot: &TimeSlot) -> Option<TimeSlotId> {
+        let owner_id = slot.get_owner_id();
+        let start_time = env::block_timestamp();
+        let end_time = start_time.saturating_add(self.config.max_future_time);
+        let utc_offset_minutes = self.utc_offset(owner_id);
+        let windows = |slot: &TimeSlot| -> Vec<Window> {
+            slot.occurrences_between(start_time, end_time, utc_offset_minutes)
+                .into_iter()
+                .map(|occurrence| (occurrence.start_time, occurrence.end_time))
+                .collect()
+        };
+        let slot_windows = windows(slot);
+
+        self.time_slots_per_owner
+            .get(owner_id)?
+            .iter()
+            .filter(|other_id| *other_id != slot.id)
+            .filter_map(|other_id| self.load_time_slot(&other_id))
+            .filter(|other| other.slot_type == slot.slot_type)
+            .find(|other| schedule::sorted_windows_overlap(&slot_windows, &windows(other)))
+            .map(|other| other.id)
+    }
+
+    // === Scheduling ===
+    pub fn preview_schedule(&self, owner_id: AccountId, horizon: u64) -> SchedulePlanResponse {
+        Response::from_result(self.build_schedule_plan(&owner_id, horizon), |e| e)
+    }
+
+    pub fn plan_schedule(&mut self, horizon: u64, dry_run: Option<bool>) -> SchedulePlanResponse {
+        self.mutate(|contract| contract.internal_plan_schedule(horizon, dry_run))
+    }
+
+    fn internal_plan_schedule(&mut self, horizon: u64, dry_run: Option<bool>) -> SchedulePlanResponse {
+        let owner_id = env::predecessor_account_id();
+
+        let plan = match self.build_schedule_plan(&owner_id, horizon) {
+            Ok(plan) => plan,
+            Err(e) => return Response::Error(e)
+        };
+
+        if dry_run.unwrap_or(false) {
+            return Response::Success(plan);
+        }
+
+        // Validate every placement before writing any of them
+        let mut updated_tasks = Vec::with_capacity(plan.scheduled.len());
+        for scheduled in &plan.scheduled {
+            let mut task = match self.load_task(&scheduled.task_id) {
+                Some(t) => t,
+                None => return Response::Error(ContractError::NotFound(
+                    "Task".to_string(),
+                    format!("Task {} not found", scheduled.task_id)
+                ))
+            };
+
+            task.time_slots = scheduled.time_slots.clone();
+            if let Err(e) = task.validate(&self.config) {
+                return Response::Error(e.into());
+            }
+            updated_tasks.push(task);
+        }
+
+        for task in updated_tasks {
+            self.save_task(&task);
+            ContractEvent::TaskUpdated {
+                task_id: task.id.clone(),
+                owner_id: owner_id.clone(),
+            }.emit();
+        }
+
+        Response::Success(plan)
+    }
+
+    fn owner_slot_occurrences(
+        &self,
+        owner_id: &AccountId,
+        start_time: u64,
+        end_time: u64,
+        slot_type: Option<SlotType>
+    ) -> Vec<SlotOccurrence> {
+        let slot_set = match self.time_slots_per_owner.get(owner_id) {
+            Some(s) => s,
+            None => return Vec::new()
+        };
+        let utc_offset_minutes = self.utc_offset(owner_id);
+
+        let mut occurrences: Vec<SlotOccurrence> = slot_set
+            .iter()
+            .filter_map(|slot_id| self.load_time_slot(&slot_id))
+            .filter(|slot| slot_type.as_ref().is_none_or(|t| slot.slot_type == *t))
+            .flat_map(|slot| slot.occurrences_between(start_time, end_time, utc_offset_minutes))
+            .collect();
+
+        occurrences.sort_by_key(|occurrence| (occurrence.start_time, occurrence.end_time));
+        occurrences
+    }
+
+    fn check_schedule_conflicts(&self, task: &Task, allow_overlap: Option<bool>) -> Result<(), ContractError> {
+        if allow_overlap.unwrap_or(false) || task.time_slots.is_empty() {
+            return Ok(());
+        }
+
+        let conflicts = self.find_schedule_conflicts(task);
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(ContractError::ScheduleConflict(conflicts))
+        }
+    }
+
+    fn find_schedule_conflicts(&self, task: &Task) -> Vec<ScheduleConflict> {
+        let owner_id = task.get_owner_id();
+        let windows: Vec<Window> = task.time_slots
+            .iter()
+            .map(|slot| (slot.start_time, slot.end_time))
+            .collect();
+        let mut conflicts = Vec::new();
+
+        if let Some(task_set) = self.tasks_per_owner.get(owner_id) {
+            let other_tasks = task_set
+                .iter()
+                .filter(|other_id| *other_id != task.id)
+                .filter_map(|other_id| self.load_task(&other_id))
+                .filter(|other| other.state != TaskState::Completed);
+
+            for other in other_tasks {
+                for slot in &other.time_slots {
+                    let other_window = (slot.start_time, slot.end_time);
+                    if windows.iter().any(|window| schedule::windows_overlap(*window, other_window)) {
+                        conflicts.push(ScheduleConflict {
+                            source: ConflictSource::Task(other.id.clone()),
+                            start_time: slot.start_time,
+                            end_time: slot.end_time,
+                        });
+                    }
+                }
+            }
+        }
+
+        let range_start = windows.iter().map(|window| window.0).min().unwrap_or_default();
+        let range_end = windows.iter().map(|window| window.1).max().unwrap_or_default();
+        for occurrence in self.owner_slot_occurrences(owner_id, range_start, range_end, Some(SlotType::Break)) {
+            let break_window = (occurrence.start_time, occurrence.end_time);
+            if windows.iter().any(|window| schedule::windows_overlap(*window, break_window)) {
+                conflicts.push(ScheduleConflict {
+                    source: ConflictSource::Break(occurrence.slot_id),
+                    start_time: occurrence.start_time,
+                    end_time: occurrence.end_time,
+                });
+            }
+        }
+
+        conflicts
+    }
+
+    fn build_schedule_plan(&self, owner_id: &AccountId, horizon: u64) -> Result<SchedulePlan, ContractError> {
+        let current_time = env::block_timestamp();
+        if horizon <= current_time || horizon > current_time.saturating_add(self.config.max_slot_future_time) {
+            return Err(ContractError::ValidationError(
+                "Schedule".to_string(),
+                "Horizon must be in the future and within the slot planning window".to_string(),
+                Some(format!("horizon: {}", horizon))
+            ));
+        }
+
+        let working_windows: Vec<Window> = self
+            .owner_slot_occurrences(owner_id, current_time, horizon, Some(SlotType::WorkingHours))
+            .into_iter()
+            .map(|occurrence| (occurrence.start_time.max(current_time), occurrence.end_time.min(horizon)))
+            .collect();
+
+        let mut busy_windows: Vec<Window> = self
+            .owner_slot_occurrences(owner_id, current_time, horizon, Some(SlotType::Break))
+            .into_iter()
+            .map(|occurrence| (occurrence.start_time, occurrence.end_time))
+            .collect();
+
+        let tasks: Vec<Task> = self.tasks_per_owner
+            .get(owner_id)
+            .map(|task_set| task_set.iter().filter_map(|task_id| self.load_task(&task_id)).collect())
+            .unwrap_or_default();
+
+        busy_windows.extend(
+            tasks.iter()
+                .filter(|task| task.state != TaskState::Completed)
+                .flat_map(|task| task.time_slots.iter().map(|slot| (slot.start_time, slot.end_time)))
+        );
+
+        let free_windows = schedule::subtract_windows(working_windows, &busy_windows);
+        Ok(schedule::plan_tasks(&tasks, free_windows, current_time, &self.config))
+    }
+}
+
+// near-sdk 5.14 doesn't copy #[allow] onto the generated ContractExt methods, so the
+// entry points taking more than seven arguments get a module to scope it to
+#[allow(clippy::too_many_arguments)]
+mod task_entry_points {
+    use near_sdk::near;
+
+    use crate::{Contract, ContractExt, TaskActionResponse};
+    use crate::models::{TaskId, Priority, TaskTimeSlot, RecurrencePattern};
+
+    #[near]
+    impl Contract {
+        pub fn add_task(
+            &mut self,
+            title: String,
+            description: String,
+            priority: Priority,
+            deadline: u64,
+            estimated_time: u32,
+            time_slots: Option<Vec<TaskTimeSlot>>,
+            parent_task_id: Option<TaskId>,
+            recurrence_pattern: Option<RecurrencePattern>,
+            allow_overlap: Option<bool>,
+        ) -> TaskActionResponse {
+            self.mutate(|contract| contract.internal_add_task(
+                title, description, priority, deadline, estimated_time,
+                time_slots, parent_task_id, recurrence_pattern, allow_overlap
+            ))
+        }
+
+        pub fn update_task(
+            &mut self,
+            task_id: TaskId,
+            title: String,
+            description: String,
+            priority: Priority,
+            deadline: u64,
+            estimated_time: u32,
+            time_slots: Option<Vec<TaskTimeSlot>>,
+            allow_overlap: Option<bool>,
+        ) -> TaskActionResponse {
+            self.mutate(|contract| contract.internal_update_task(
+                task_id, title, description, priority, deadline,
+                estimated_time, time_slots, allow_overlap
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+    use crate::models::DayOfWeek;
+
+    const NOW: u64 = 1_700_000_000_000_000_000;
+    const DAY: u64 = 86_400_000_000_000;
+
+    fn context(account_id: &AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id("contract.near".parse().unwrap())
+            .predecessor_account_id(account_id.clone())
+            .signer_account_id(account_id.clone())
+            .attached_deposit(NearToken::from_near(1))
+            .block_timestamp(NOW);
+        builder
+    }
+
+    // Fresh gas counter for every call, storage carries over
+    fn call_as(account_id: &AccountId) {
+        testing_env!(context(account_id).build());
+    }
+
+    fn expect<T>(response: Response<T, ContractError>) -> T {
+        match response {
+            Response::Success(value) => value,
+            Response::Error(e) => panic!("{}", e),
+        }
+    }
+
+    fn add_habit_task(contract: &mut Contract, account_id: &AccountId) -> TaskId {
+        call_as(account_id);
+        contract.storage_deposit(None, None);
+        call_as(account_id);
+        expect(contract.add_task(
+            "Stretch".to_string(),
+            String::new(),
+            Priority::Low,
+            NOW + DAY,
+            30,
+            None,
+            None,
+            Some(RecurrencePattern::new_daily()),
+            None,
+        ))
+    }
+
+    // Gas used by one habit completion after `other_owners` accounts created habits
+    fn completion_gas(other_owners: usize) -> u64 {
+        // Every run starts from empty storage
+        near_sdk::mock::with_mocked_blockchain(|blockchain| blockchain.take_storage());
+        let admin: AccountId = "admin.near".parse().unwrap();
+        call_as(&admin);
+        let mut contract = Contract::new(Some(admin));
+
+        for index in 0..other_owners {
+            let account_id: AccountId = format!("user{:04}.near", index).parse().unwrap();
+            add_habit_task(&mut contract, &account_id);
+        }
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let task_id = add_habit_task(&mut contract, &alice);
+        call_as(&alice);
+        expect(contract.start_task(task_id.clone(), NOW, None));
+
+        call_as(&alice);
+        let before = env::used_gas();
+        expect(contract.complete_task(task_id));
+        env::used_gas().as_gas() - before.as_gas()
+    }
+
+    #[test]
+    fn habit_completion_gas_is_independent_of_other_habits() {
+        let baseline = completion_gas(0);
+        for other_owners in [10, 100] {
+            // Allow for key lengths and tree depth, not for walking other habits
+            let gas = completion_gas(other_owners);
+            assert!(
+                gas.abs_diff(baseline) <= baseline / 100,
+                "{} other habits: {} gas, baseline {}", other_owners, gas, baseline
+            );
+        }
+    }
+
+    #[test]
+    fn unregister_removes_every_account_record() {
+        let admin: AccountId = "admin.near".parse().unwrap();
+        call_as(&admin);
+        let mut contract = Contract::new(Some(admin));
+
+        let alice: AccountId = "alice.near".parse().unwrap();
+        call_as(&alice);
+        contract.storage_deposit(None, None);
+        call_as(&alice);
+        expect(contract.set_utc_offset(60));
+        call_as(&alice);
+        let task_id = expect(contract.add_task(
+            "Read".to_string(), String::new(), Priority::High, NOW + DAY, 30, None, None, None, None,
+        ));
+        call_as(&alice);
+        expect(contract.start_task(task_id.clone(), NOW, None));
+        call_as(&alice);
+        expect(contract.complete_task(task_id.clone()));
+        assert!(contract.task_completions.get(&task_id).is_some());
+        call_as(&alice);
+        expect(contract.delete_task(task_id.clone(), None));
+        assert!(contract.task_completions.get(&task_id).is_none());
+
+        testing_env!(context(&alice).attached_deposit(NearToken::from_yoctonear(1)).build());
+        assert!(contract.storage_unregister(None));
+
+        let report = expect(contract.get_storage_report(alice.clone()));
+        assert_eq!(report.account.bytes, 0);
+        assert_eq!(report.points_ledger.count, 0);
+        assert_eq!(report.balance, None);
+        assert_eq!(expect(contract.get_reward_points(&alice)), 0);
+    }
+
+    fn registered(account_id: &AccountId) -> Contract {
+        call_as(account_id);
+        let mut contract = Contract::new(None);
+        contract.storage_deposit(None, None);
+        contract
+    }
+
+    fn add_one_off_task(contract: &mut Contract, account_id: &AccountId, title: &str) -> TaskId {
+        call_as(account_id);
+        expect(contract.add_task(
+            title.to_string(), String::new(), Priority::Medium, NOW + DAY, 30, None, None, None, None,
+        ))
+    }
+
+    fn run_task(contract: &mut Contract, account_id: &AccountId, task_id: &TaskId) {
+        call_as(account_id);
+        expect(contract.start_task(task_id.clone(), NOW, Some(true)));
+        call_as(account_id);
+        expect(contract.complete_task(task_id.clone()));
+    }
+
+    fn is_state_error<T>(response: Response<T, ContractError>) -> bool {
+        matches!(response, Response::Error(ContractError::StateError(..)))
+    }
+
+    #[test]
+    fn dependency_cycles_are_rejected() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        let research = add_one_off_task(&mut contract, &alice, "Research");
+        let draft = add_one_off_task(&mut contract, &alice, "Draft");
+        let review = add_one_off_task(&mut contract, &alice, "Review");
+
+        call_as(&alice);
+        expect(contract.add_task_dependency(draft.clone(), research.clone()));
+        call_as(&alice);
+        expect(contract.add_task_dependency(review.clone(), draft.clone()));
+
+        for (task_id, blocker_id) in [(&research, &review), (&research, &draft), (&draft, &draft)] {
+            call_as(&alice);
+            assert!(matches!(
+                contract.add_task_dependency(task_id.clone(), blocker_id.clone()),
+                Response::Error(ContractError::ValidationError(..))
+            ));
+        }
+        assert!(expect(contract.get_task_blockers(research.clone())).is_empty());
+        assert_eq!(expect(contract.get_task_blockers(draft)), [research]);
+    }
+
+    #[test]
+    fn blocked_tasks_cannot_start_or_complete() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        let research = add_one_off_task(&mut contract, &alice, "Research");
+        let draft = add_one_off_task(&mut contract, &alice, "Draft");
+
+        call_as(&alice);
+        expect(contract.add_task_dependency(draft.clone(), research.clone()));
+        call_as(&alice);
+        assert!(is_state_error(contract.start_task(draft.clone(), NOW, Some(true))));
+
+        run_task(&mut contract, &alice, &research);
+        call_as(&alice);
+        expect(contract.start_task(draft.clone(), NOW, Some(true)));
+
+        // A blocker added once the task is under way still holds back its completion
+        let sources = add_one_off_task(&mut contract, &alice, "Sources");
+        call_as(&alice);
+        expect(contract.add_task_dependency(draft.clone(), sources.clone()));
+        call_as(&alice);
+        assert!(is_state_error(contract.complete_task(draft.clone())));
+
+        run_task(&mut contract, &alice, &sources);
+        call_as(&alice);
+        expect(contract.complete_task(draft));
+    }
+
+    #[test]
+    fn task_order_puts_blockers_first_and_is_empty_without_open_tasks() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        assert!(expect(contract.get_task_order(alice.clone())).is_empty());
+
+        let draft = add_one_off_task(&mut contract, &alice, "Draft");
+        let research = add_one_off_task(&mut contract, &alice, "Research");
+        call_as(&alice);
+        expect(contract.add_task_dependency(draft.clone(), research.clone()));
+
+        let order: Vec<TaskId> = expect(contract.get_task_order(alice.clone()))
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(order, [research.clone(), draft.clone()]);
+
+        run_task(&mut contract, &alice, &research);
+        run_task(&mut contract, &alice, &draft);
+        assert!(expect(contract.get_task_order(alice)).is_empty());
+    }
+
+    fn add_slot(
+        contract: &mut Contract,
+        account_id: &AccountId,
+        start_minutes: u32,
+        end_minutes: u32,
+        slot_type: SlotType,
+        day: DayOfWeek,
+    ) -> TimeSlotActionResponse {
+        call_as(account_id);
+        contract.add_time_slot(start_minutes, end_minutes, slot_type, RecurrencePattern::new_custom(vec![day]))
+    }
+
+    #[test]
+    fn time_slots_only_overlap_when_their_occurrences_do() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        call_as(&alice);
+        let mut contract = Contract::new(None);
+        contract.storage_deposit(None, None);
+
+        let monday = expect(add_slot(&mut contract, &alice, 540, 600, SlotType::WorkingHours, DayOfWeek::Monday));
+        let tuesday = expect(add_slot(&mut contract, &alice, 540, 600, SlotType::WorkingHours, DayOfWeek::Tuesday));
+        assert!(matches!(
+            add_slot(&mut contract, &alice, 570, 630, SlotType::WorkingHours, DayOfWeek::Monday),
+            Response::Error(ContractError::Operation(_))
+        ));
+        expect(add_slot(&mut contract, &alice, 570, 630, SlotType::Break, DayOfWeek::Monday));
+
+        // Friday night runs into Saturday morning
+        let friday_night = expect(add_slot(&mut contract, &alice, 1320, 120, SlotType::WorkingHours, DayOfWeek::Friday));
+        assert!(matches!(
+            add_slot(&mut contract, &alice, 60, 180, SlotType::WorkingHours, DayOfWeek::Saturday),
+            Response::Error(ContractError::Operation(_))
+        ));
+        expect(add_slot(&mut contract, &alice, 120, 180, SlotType::WorkingHours, DayOfWeek::Saturday));
+
+        call_as(&alice);
+        assert!(matches!(
+            contract.update_time_slot(tuesday.clone(), 570, 630, RecurrencePattern::new_custom(vec![DayOfWeek::Monday])),
+            Response::Error(ContractError::Operation(_))
+        ));
+        call_as(&alice);
+        expect(contract.update_time_slot(tuesday, 600, 660, RecurrencePattern::new_custom(vec![DayOfWeek::Monday])));
+
+        let overnight: Vec<TimeSlotId> = expect(contract.get_time_slots_by_timeframe(
+            alice.clone(), 60, 90, Some(SlotType::WorkingHours),
+        )).into_iter().map(|slot| slot.id).collect();
+        assert_eq!(overnight, [friday_night]);
+        let mornings = expect(contract.get_time_slots_by_timeframe(alice, 1380, 560, Some(SlotType::WorkingHours)));
+        assert!(mornings.iter().any(|slot| slot.id == monday));
+    }
+
+    #[test]
+    fn task_queries_page_through_every_state_in_deadline_order() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        let mut add = |title: &str, priority, days| {
+            call_as(&alice);
+            expect(contract.add_task(
+                title.to_string(), String::new(), priority, NOW + days * DAY, 30, None, None, None, None,
+            ))
+        };
+        let a = add("A", Priority::High, 1);
+        let d = add("D", Priority::High, 4);
+        let c = add("C", Priority::Low, 3);
+        let b = add("B", Priority::Low, 2);
+        run_task(&mut contract, &alice, &b);
+
+        let page = |contract: &Contract, query: TaskQuery| {
+            let page = expect(contract.query_tasks(alice.clone(), Some(query)));
+            (page.tasks.into_iter().map(|task| task.id).collect::<Vec<TaskId>>(), page.total)
+        };
+        assert_eq!(page(&contract, TaskQuery { limit: Some(2), ..Default::default() }), (vec![a.clone(), b.clone()], 4));
+        assert_eq!(
+            page(&contract, TaskQuery { from_index: Some(2), limit: Some(2), ..Default::default() }),
+            (vec![c.clone(), d.clone()], 4)
+        );
+        assert_eq!(
+            page(&contract, TaskQuery { limit: Some(1), descending: Some(true), ..Default::default() }),
+            (vec![d.clone()], 4)
+        );
+        assert_eq!(
+            page(&contract, TaskQuery { state: Some(TaskState::Created), ..Default::default() }),
+            (vec![a.clone(), c.clone(), d.clone()], 3)
+        );
+        assert_eq!(
+            page(&contract, TaskQuery { priority: Some(Priority::High), limit: Some(1), ..Default::default() }),
+            (vec![a.clone()], 2)
+        );
+        assert_eq!(
+            page(&contract, TaskQuery { deadline_from: Some(NOW + 2 * DAY), deadline_to: Some(NOW + 3 * DAY), ..Default::default() }),
+            (vec![b.clone(), c.clone()], 2)
+        );
+        assert_eq!(
+            page(&contract, TaskQuery { sort_by: Some(TaskSortKey::Title), descending: Some(true), ..Default::default() }),
+            (vec![d, c, b, a], 4)
+        );
+    }
+
+    #[test]
+    fn habit_changes_move_the_task_deadline() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        call_as(&alice);
+        let task_id = expect(contract.add_task(
+            "Run".to_string(), String::new(), Priority::Medium, NOW + 5 * DAY, 30, None, None, None, None,
+        ));
+        let deadline = |contract: &Contract| contract.load_task(&task_id).unwrap().deadline;
+
+        call_as(&alice);
+        let habit_id = expect(contract.add_habit(task_id.clone(), RecurrencePattern::new_daily()));
+        assert_eq!(deadline(&contract), NOW + DAY);
+
+        // NOW is a Tuesday, so the next Friday is three days out
+        call_as(&alice);
+        expect(contract.update_habit(habit_id, RecurrencePattern::new_custom(vec![DayOfWeek::Friday])));
+        assert_eq!(deadline(&contract), NOW + 3 * DAY);
+        let upcoming = expect(contract.get_upcoming_tasks(alice.clone(), None));
+        assert_eq!(upcoming[0].deadline, NOW + 3 * DAY);
+    }
+
+    #[test]
+    fn points_changes_are_checked_and_recorded_in_the_ledger() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        let task_id = add_one_off_task(&mut contract, &alice, "Read");
+        run_task(&mut contract, &alice, &task_id);
+        call_as(&alice);
+        expect(contract.credit_points(&alice, 118, PointsSource::Task(task_id.clone())));
+
+        // Two freezes at the default cost of 50 each
+        call_as(&alice);
+        assert_eq!(expect(contract.buy_streak_freezes(2)), 2);
+        assert_eq!(expect(contract.get_reward_points(&alice)), 20);
+
+        call_as(&alice);
+        assert!(matches!(
+            contract.buy_streak_freezes(1),
+            Response::Error(ContractError::PointsError(PointsError::InsufficientPoints { balance: 20, required: 50 }))
+        ));
+        call_as(&alice);
+        assert!(matches!(contract.buy_streak_freezes(0), Response::Error(ContractError::ValidationError(..))));
+        call_as(&alice);
+        assert!(matches!(contract.buy_streak_freezes(4), Response::Error(ContractError::ValidationError(..))));
+        assert!(matches!(
+            contract.credit_points(&alice, u32::MAX, PointsSource::StreakFreeze),
+            Response::Error(ContractError::PointsError(PointsError::Overflow { balance: 20, .. }))
+        ));
+        assert_eq!(expect(contract.credit_points(&alice, 0, PointsSource::StreakFreeze)), 20);
+        assert_eq!(expect(contract.get_streak_freezes(alice.clone())), 2);
+        assert_eq!(expect(contract.get_reward_points(&alice)), 20);
+
+        // Failed and zero changes leave no entry
+        let history = expect(contract.get_points_history(alice.clone(), None, None));
+        assert_eq!(history.total, 3);
+        assert_eq!(history.entries, [
+            PointsEntry::new(PointsEntryKind::Credit, PointsSource::Task(task_id.clone()), 2, NOW, 2),
+            PointsEntry::new(PointsEntryKind::Credit, PointsSource::Task(task_id), 118, NOW, 120),
+            PointsEntry::new(PointsEntryKind::Debit, PointsSource::StreakFreeze, 100, NOW, 20),
+        ]);
+        let page = expect(contract.get_points_history(alice, Some(2), Some(5)));
+        assert_eq!((page.entries.len(), page.total), (1, 3));
+    }
+
+    #[test]
+    fn penalty_policies_shape_overdue_and_late_completion_points() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        call_as(&alice);
+        expect(contract.set_penalty_policy(Some(PenaltyPolicy { overdue_penalty_percent: 50, late_completion_percent: 50 })));
+
+        // High priority hours are worth 6 points
+        let add_started = |contract: &mut Contract, title: &str| {
+            call_as(&alice);
+            let task_id = expect(contract.add_task(
+                title.to_string(), String::new(), Priority::High, NOW + DAY, 60, None, None, None, None,
+            ));
+            call_as(&alice);
+            expect(contract.start_task(task_id.clone(), NOW, Some(true)));
+            task_id
+        };
+        let on_time = add_started(&mut contract, "On time");
+        let late = add_started(&mut contract, "Late");
+        let unpenalised = add_started(&mut contract, "Unpenalised");
+        call_as(&alice);
+        expect(contract.complete_task(on_time));
+        assert_eq!(expect(contract.get_reward_points(&alice)), 6);
+
+        // Half the reward scaled by 150% for High priority, then half of it back late
+        let after_deadline = context(&alice).block_timestamp(NOW + 2 * DAY).build();
+        testing_env!(after_deadline.clone());
+        expect(contract.mark_task_overdue(late.clone()));
+        assert_eq!(expect(contract.get_reward_points(&alice)), 2);
+        testing_env!(after_deadline.clone());
+        expect(contract.complete_task(late));
+        assert_eq!(expect(contract.get_reward_points(&alice)), 5);
+
+        // Without a policy overdue tasks cost nothing and still earn in full
+        testing_env!(after_deadline.clone());
+        expect(contract.set_penalty_policy(None));
+        testing_env!(after_deadline.clone());
+        expect(contract.mark_task_overdue(unpenalised.clone()));
+        testing_env!(after_deadline);
+        expect(contract.complete_task(unpenalised));
+        assert_eq!(expect(contract.get_reward_points(&alice)), 11);
+    }
+
+    #[test]
+    fn habit_stats_bound_their_periods_and_range() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        call_as(&alice);
+        let mut contract = Contract::new(None);
+        let task_id = add_habit_task(&mut contract, &alice);
+        let habit_id = contract.habit_by_task.get(&task_id).unwrap();
+        let today = NOW - NOW % DAY;
+
+        let stats = |periods, start_time, end_time| {
+            call_as(&alice);
+            contract.get_habit_stats(habit_id.clone(), periods, start_time, end_time)
+        };
+        let is_validation_error = |response: HabitStatsResponse| {
+            matches!(response, Response::Error(ContractError::ValidationError(..)))
+        };
+        assert!(is_validation_error(stats(0, today, today + DAY)));
+        assert!(is_validation_error(stats(MAX_STATS_PERIODS + 1, today, today + DAY)));
+        assert!(is_validation_error(stats(1, today, today)));
+        assert!(is_validation_error(stats(1, today, today + (MAX_STATS_DAYS + 1) * DAY)));
+        assert!(is_validation_error(stats(1, today, today + MAX_STATS_DAYS * DAY + 1)));
+
+        let longest = expect(stats(MAX_STATS_PERIODS, today, today + MAX_STATS_DAYS * DAY));
+        assert_eq!(longest.days.len() as u64, MAX_STATS_DAYS);
+        assert!(longest.days.iter().all(|day| day.scheduled));
+    }
+
+    #[test]
+    fn sweeps_only_walk_tasks_that_are_not_overdue_yet() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let mut contract = registered(&alice);
+        let mut task_ids: Vec<TaskId> = ["Read", "Write", "Plan"]
+            .into_iter()
+            .map(|title| add_one_off_task(&mut contract, &alice, title))
+            .collect();
+        task_ids.sort();
+        call_as(&alice);
+        let later = expect(contract.add_task(
+            "Later".to_string(), String::new(), Priority::Medium, NOW + 3 * DAY, 30, None, None, None, None,
+        ));
+
+        let sweep = |contract: &mut Contract| {
+            testing_env!(context(&alice).block_timestamp(NOW + 2 * DAY).build());
+            let mut swept = expect(contract.sweep_overdue(alice.clone(), Some(2))).swept;
+            swept.sort();
+            swept
+        };
+        let first = sweep(&mut contract);
+        assert_eq!(first.len(), 2);
+        let second = sweep(&mut contract);
+        assert_eq!(second.len(), 1);
+        assert!(sweep(&mut contract).is_empty());
+        let mut swept = [first, second].concat();
+        swept.sort();
+        assert_eq!(swept, task_ids);
+
+        // Overdue tasks left the open trees; the overdue view still lists them
+        let open = |state| contract.tasks_by_state.get(&(alice.clone(), state)).map_or(0, |tree| tree.len());
+        assert_eq!(open(TaskState::Created), 1);
+        assert_eq!(open(TaskState::Overdue), 3);
+        testing_env!(context(&alice).block_timestamp(NOW + 2 * DAY).build());
+        assert_eq!(expect(contract.get_overdue_tasks(alice.clone(), None)).len(), 3);
+        let upcoming: Vec<TaskId> = expect(contract.get_upcoming_tasks(alice.clone(), None))
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(upcoming, [later]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Force unregistration is not supported")]
+    fn unregister_rejects_force() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        call_as(&alice);
+        let mut contract = Contract::new(None);
+        contract.storage_deposit(None, None);
+
+        testing_env!(context(&alice).attached_deposit(NearToken::from_yoctonear(1)).build());
+        contract.storage_unregister(Some(true));
+    }
+}
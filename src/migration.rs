@@ -0,0 +1,323 @@
+use near_sdk::{
+    env, near, AccountId, Gas,
+    collections::{LookupMap, UnorderedMap, UnorderedSet},
+};
+
+use crate::Contract;
+use crate::models::{
+    TaskId, HabitId, RewardId, TimeSlotId, Task, Habit,
+    VersionedTask, VersionedHabit, VersionedReward, VersionedTimeSlot,
+};
+
+pub const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+pub const STATE_VERSION: u8 = 1;
+pub const MIGRATE_GAS: Gas = Gas::from_tgas(100);
+
+// === Previous Layouts ===
+// Deployed before state versioning. Entity maps use the versioned wrappers,
+// which read the untagged records written at the time
+#[near(serializers = [borsh])]
+pub struct ContractV0 {
+    tasks: UnorderedMap<TaskId, VersionedTask>,
+    tasks_per_owner: LookupMap<AccountId, UnorderedSet<TaskId>>,
+    habits: UnorderedMap<HabitId, VersionedHabit>,
+    habits_per_owner: LookupMap<AccountId, UnorderedSet<HabitId>>,
+    task_completions: LookupMap<TaskId, Vec<u64>>,
+    reward_points: LookupMap<AccountId, u32>,
+    rewards: UnorderedMap<RewardId, VersionedReward>,
+    rewards_per_owner: LookupMap<AccountId, UnorderedSet<RewardId>>,
+    time_slots: UnorderedMap<TimeSlotId, VersionedTimeSlot>,
+    time_slots_per_owner: LookupMap<AccountId, UnorderedSet<TimeSlotId>>,
+}
+
+// === Versioned State ===
+// Read once per migration, so the variant sizes don't matter
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedState {
+    V0(ContractV0),
+    V1(Contract),
+}
+
+impl VersionedState {
+    // State without a version key predates versioning
+    pub fn read() -> Self {
+        let version = env::storage_read(STATE_VERSION_KEY).and_then(|bytes| bytes.first().copied());
+        match version {
+            None => Self::V0(read_state()),
+            Some(1) => Self::V1(read_state()),
+            Some(version) => env::panic_str(&format!("Unknown state version: {}", version)),
+        }
+    }
+
+    pub fn into_current(self) -> Contract {
+        match self {
+            Self::V0(contract) => contract.into(),
+            Self::V1(contract) => contract,
+        }
+    }
+}
+
+pub fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &[STATE_VERSION]);
+}
+
+fn read_state<T: near_sdk::borsh::BorshDeserialize>() -> T {
+    env::state_read().unwrap_or_else(|| env::panic_str("No contract state to migrate"))
+}
+
+// === Conversions ===
+// Collections added since the baseline start empty, apart from the task and habit
+// indices built here, and the contract account becomes the admin. Owners register
+// a storage deposit before writing again. Balances earned before the points
+// ledger carry no history; the first new entry records the running total
+impl From<ContractV0> for Contract {
+    fn from(old: ContractV0) -> Self {
+        let mut contract = Self {
+            tasks: old.tasks,
+            tasks_per_owner: old.tasks_per_owner,
+            habits: old.habits,
+            habits_per_owner: old.habits_per_owner,
+            task_completions: old.task_completions,
+            reward_points: old.reward_points,
+            rewards: old.rewards,
+            rewards_per_owner: old.rewards_per_owner,
+            time_slots: old.time_slots,
+            time_slots_per_owner: old.time_slots_per_owner,
+            ..Contract::new(None)
+        };
+
+        // complete_task only finds a task's habit through this index
+        let habit_tasks: Vec<(TaskId, HabitId)> = contract.habits
+            .iter()
+            .map(|(habit_id, habit)| (Habit::from(habit).task_id, habit_id))
+            .collect();
+        for (task_id, habit_id) in habit_tasks {
+            contract.habit_by_task.insert(&task_id, &habit_id);
+        }
+
+        // The state views, deadline views and overdue sweeps walk these trees
+        let tasks: Vec<Task> = contract.tasks.values().map(Task::from).collect();
+        for task in &tasks {
+            contract.update_task_indices(None, Some(task));
+        }
+        contract
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::borsh;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+    use crate::Response;
+    use crate::models::{
+        Reward, TimeSlot, Priority, TaskState, RewardState, TaskTimeSlot,
+        Frequency, DayOfWeek, SlotType, ContractConfig, Role, Ownable, TaskQuery,
+    };
+
+    const NOW: u64 = 1_700_000_000_000_000_000;
+    const DAY: u64 = 86_400_000_000_000;
+
+    // The records and contract struct as deployed at the baseline. Tasks and
+    // rewards have kept their layout, so the current types write them
+    #[near(serializers = [borsh])]
+    struct BaselineRecurrence {
+        frequency: Frequency,
+        interval: Option<u32>,
+        specific_days: Option<Vec<DayOfWeek>>,
+    }
+
+    #[near(serializers = [borsh])]
+    struct BaselineHabit {
+        id: HabitId,
+        task_id: TaskId,
+        recurrence: BaselineRecurrence,
+        streak: u32,
+        last_completed: u64,
+        owner_id: AccountId,
+    }
+
+    #[near(serializers = [borsh])]
+    struct BaselineTimeSlot {
+        id: TimeSlotId,
+        start_minutes: u32,
+        end_minutes: u32,
+        duration: Option<u32>,
+        recurrence: BaselineRecurrence,
+        owner_id: AccountId,
+        slot_type: SlotType,
+    }
+
+    #[near(serializers = [borsh])]
+    struct BaselineContract {
+        tasks: UnorderedMap<TaskId, Task>,
+        tasks_per_owner: LookupMap<AccountId, UnorderedSet<TaskId>>,
+        habits: UnorderedMap<HabitId, BaselineHabit>,
+        habits_per_owner: LookupMap<AccountId, UnorderedSet<HabitId>>,
+        task_completions: LookupMap<TaskId, Vec<u64>>,
+        reward_points: LookupMap<AccountId, u32>,
+        rewards: UnorderedMap<RewardId, Reward>,
+        rewards_per_owner: LookupMap<AccountId, UnorderedSet<RewardId>>,
+        time_slots: UnorderedMap<TimeSlotId, BaselineTimeSlot>,
+        time_slots_per_owner: LookupMap<AccountId, UnorderedSet<TimeSlotId>>,
+    }
+
+    fn owner_set(prefix: &str, owner_id: &AccountId, id: &str) -> UnorderedSet<String> {
+        let mut set = UnorderedSet::new(format!("{}{}", prefix, owner_id).into_bytes());
+        set.insert(&id.to_string());
+        set
+    }
+
+    #[test]
+    fn migrate_converts_the_baseline_state() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("contract.near".parse().unwrap())
+            .predecessor_account_id("contract.near".parse().unwrap())
+            .block_timestamp(NOW)
+            .build());
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let config = ContractConfig::default();
+
+        let task_id = format!("task-{}-{}", alice, NOW);
+        let habit_id = format!("habit-{}-{}", alice, NOW);
+        let reward_id = format!("reward-{}-{}", alice, NOW);
+        let slot_id = format!("time_slot-{}-{}", alice, NOW);
+
+        let task = Task::new(
+            task_id.clone(), "Read".to_string(), "A chapter".to_string(), Priority::High,
+            NOW + DAY, 45, vec![TaskTimeSlot { start_time: NOW + 60, end_time: NOW + 120 }],
+            alice.clone(), &config,
+        ).unwrap_or_else(|_| panic!("invalid task"));
+        let reward = Reward::new(
+            reward_id.clone(), "Coffee".to_string(), "Downtown".to_string(), 30, alice.clone(), &config,
+        ).unwrap_or_else(|_| panic!("invalid reward"));
+        let days = vec![DayOfWeek::Monday, DayOfWeek::Thursday];
+
+        let mut baseline = BaselineContract {
+            tasks: UnorderedMap::new(b"t".to_vec()),
+            tasks_per_owner: LookupMap::new(b"to".to_vec()),
+            habits: UnorderedMap::new(b"h".to_vec()),
+            habits_per_owner: LookupMap::new(b"ho".to_vec()),
+            task_completions: LookupMap::new(b"tc".to_vec()),
+            reward_points: LookupMap::new(b"rp".to_vec()),
+            rewards: UnorderedMap::new(b"r".to_vec()),
+            rewards_per_owner: LookupMap::new(b"ro".to_vec()),
+            time_slots: UnorderedMap::new(b"ts".to_vec()),
+            time_slots_per_owner: LookupMap::new(b"tso".to_vec()),
+        };
+        baseline.tasks.insert(&task_id, &task);
+        baseline.tasks_per_owner.insert(&alice, &owner_set("to", &alice, &task_id));
+        baseline.habits.insert(&habit_id, &BaselineHabit {
+            id: habit_id.clone(),
+            task_id: task_id.clone(),
+            recurrence: BaselineRecurrence {
+                frequency: Frequency::Custom,
+                interval: None,
+                specific_days: Some(days.clone()),
+            },
+            streak: 4,
+            last_completed: NOW - DAY,
+            owner_id: alice.clone(),
+        });
+        baseline.habits_per_owner.insert(&alice, &owner_set("ho", &alice, &habit_id));
+        baseline.task_completions.insert(&task_id, &vec![NOW - 2 * DAY, NOW - DAY]);
+        baseline.reward_points.insert(&alice, &42);
+        baseline.rewards.insert(&reward_id, &reward);
+        baseline.rewards_per_owner.insert(&alice, &owner_set("ro", &alice, &reward_id));
+        baseline.time_slots.insert(&slot_id, &BaselineTimeSlot {
+            id: slot_id.clone(),
+            start_minutes: 9 * 60,
+            end_minutes: 17 * 60,
+            duration: Some(8 * 60),
+            recurrence: BaselineRecurrence { frequency: Frequency::Daily, interval: Some(2), specific_days: None },
+            owner_id: alice.clone(),
+            slot_type: SlotType::WorkingHours,
+        });
+        baseline.time_slots_per_owner.insert(&alice, &owner_set("tso", &alice, &slot_id));
+        env::state_write(&baseline);
+
+        let contract = Contract::migrate();
+
+        assert_eq!(env::storage_read(STATE_VERSION_KEY), Some(vec![STATE_VERSION]));
+
+        // Baseline collections keep their records
+        assert_eq!(contract.tasks.len(), 1);
+        let migrated_task = contract.load_task(&task_id).unwrap();
+        assert_eq!(borsh::to_vec(&migrated_task).unwrap(), borsh::to_vec(&task).unwrap());
+        assert_eq!(migrated_task.state, TaskState::Created);
+        assert!(contract.tasks_per_owner.get(&alice).unwrap().contains(&task_id));
+        assert!(matches!(
+            contract.get_tasks_by_state(alice.clone(), TaskState::Created, None, None),
+            Response::Success(page) if page.total == 1 && page.tasks[0].id == task_id
+        ));
+        assert!(matches!(
+            contract.query_tasks(alice.clone(), Some(TaskQuery { state: Some(TaskState::Created), ..Default::default() })),
+            Response::Success(page) if page.total == 1
+        ));
+        assert!(matches!(
+            contract.get_upcoming_tasks(alice.clone(), None),
+            Response::Success(tasks) if tasks.len() == 1 && tasks[0].id == task_id
+        ));
+
+        assert_eq!(contract.habits.len(), 1);
+        let habit = contract.load_habit(&habit_id).unwrap();
+        assert_eq!(habit.id, habit_id);
+        assert_eq!(habit.task_id, task_id);
+        assert_eq!(habit.recurrence.frequency, Frequency::Custom);
+        assert_eq!(habit.recurrence.interval, None);
+        assert_eq!(habit.recurrence.specific_days, Some(days));
+        assert_eq!(habit.recurrence.month_day, None);
+        assert_eq!(habit.recurrence.week_of_month, None);
+        assert_eq!(habit.recurrence.month, None);
+        assert_eq!(habit.recurrence.start, None);
+        assert_eq!(habit.recurrence.until, None);
+        assert_eq!(habit.recurrence.count, None);
+        assert_eq!(habit.streak, 4);
+        assert_eq!(habit.last_completed, NOW - DAY);
+        assert_eq!(habit.get_owner_id(), &alice);
+        assert!(!habit.paused);
+        assert_eq!(habit.next_due_day, None);
+        assert_eq!(habit.grace_minutes, 0);
+        assert!(contract.habits_per_owner.get(&alice).unwrap().contains(&habit_id));
+        assert_eq!(contract.habit_by_task.get(&task_id), Some(habit_id.clone()));
+
+        assert_eq!(contract.task_completions.get(&task_id), Some(vec![NOW - 2 * DAY, NOW - DAY]));
+        assert_eq!(contract.reward_points.get(&alice), Some(42));
+
+        assert_eq!(contract.rewards.len(), 1);
+        let migrated_reward = contract.load_reward(&reward_id).unwrap();
+        assert_eq!(borsh::to_vec(&migrated_reward).unwrap(), borsh::to_vec(&reward).unwrap());
+        assert_eq!(migrated_reward.state, RewardState::Active);
+        assert!(contract.rewards_per_owner.get(&alice).unwrap().contains(&reward_id));
+
+        assert_eq!(contract.time_slots.len(), 1);
+        let slot: TimeSlot = contract.load_time_slot(&slot_id).unwrap();
+        assert_eq!(slot.id, slot_id);
+        assert_eq!(slot.start_minutes, 9 * 60);
+        assert_eq!(slot.end_minutes, 17 * 60);
+        assert_eq!(slot.duration, Some(8 * 60));
+        assert_eq!(slot.recurrence.frequency, Frequency::Daily);
+        assert_eq!(slot.recurrence.interval, Some(2));
+        assert_eq!(slot.recurrence.specific_days, None);
+        assert_eq!(slot.recurrence.start, None);
+        assert_eq!(slot.get_owner_id(), &alice);
+        assert_eq!(slot.slot_type, SlotType::WorkingHours);
+        assert!(contract.time_slots_per_owner.get(&alice).unwrap().contains(&slot_id));
+
+        // Collections added since start empty
+        assert!(contract.account_settings.get(&alice).is_none());
+        assert!(contract.task_blockers.get(&task_id).is_none());
+        assert!(contract.task_dependents.get(&task_id).is_none());
+        assert!(contract.id_nonces.get(&alice).is_none());
+        assert!(contract.storage_accounts.get(&alice).is_none());
+        assert!(contract.points_ledger.get(&alice).is_none());
+        assert!(contract.penalty_policies.get(&alice).is_none());
+        assert!(contract.habit_history.get(&habit_id).is_none());
+        assert!(contract.streak_freezes.get(&alice).is_none());
+
+        assert_eq!(contract.roles.to_vec(), vec![("contract.near".parse().unwrap(), Role::Admin)]);
+        assert_eq!(contract.config, config);
+        assert!(!contract.paused);
+    }
+}
@@ -0,0 +1,207 @@
+use schemars::JsonSchema;
+use near_sdk::{
+    json_types::U128,
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize}};
+
+use crate::models::config::{task, reward, time::*, query::*, schedule::*, storage::*, habit::*};
+
+// === Error Hierarchy ===
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ConfigValidationError {
+    SubtaskDepth { provided_depth: u32 },
+    PageLimit { default_limit: u64, max_limit: u64 },
+    ChunkMinutes { provided_minutes: u32 },
+    ZeroLimit { limit: String },
+    LimitTooLarge { limit: String, max: u64 },
+}
+
+impl std::fmt::Display for ConfigValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SubtaskDepth { provided_depth } => {
+                write!(f, "Maximum subtask depth must be at least 1 (depth: {})", provided_depth)
+            },
+            Self::PageLimit { default_limit, max_limit } => {
+                write!(f, "Page limits must be positive with the default not above the maximum (default: {}, max: {})",
+                    default_limit, max_limit)
+            },
+            Self::ChunkMinutes { provided_minutes } => {
+                write!(f, "Minimum schedule chunk must be at least 1 minute (minutes: {})", provided_minutes)
+            },
+            Self::ZeroLimit { limit } => write!(f, "Limit {} must be greater than zero", limit),
+            Self::LimitTooLarge { limit, max } => write!(f, "Limit {} must not exceed {}", limit, max)
+        }
+    }
+}
+
+// === Core Data Structures ===
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema,
+    Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    Admin,
+    Moderator,
+}
+
+// Limits the admin can change without redeploying; defaults come from models::config
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractConfig {
+    pub task_max_title_length: u32,
+    pub task_max_description_length: u32,
+    pub reward_max_title_length: u32,
+    pub reward_max_description_length: u32,
+    pub max_estimated_minutes: u32,
+    pub max_future_time: u64,
+    pub max_slot_future_time: u64,
+    pub task_max_storage: u64,
+    pub reward_max_storage: u64,
+    pub time_slot_max_storage: u64,
+    pub habit_max_storage: u64,
+    pub max_subtask_depth: u32,
+    pub default_page_limit: u64,
+    pub max_page_limit: u64,
+    pub min_chunk_minutes: u32,
+    pub account_min_storage_bytes: u64,
+    #[schemars(with = "String")]
+    pub keeper_fee_per_task: U128,
+    // Reward points charged per streak freeze
+    pub streak_freeze_cost: u32,
+    // Streak freezes an account can hold at once
+    pub max_streak_freezes: u32,
+}
+
+// === Core Implementations ===
+impl Role {
+    // Admins can do everything moderators can
+    pub fn grants(self, required: Role) -> bool {
+        self == Role::Admin || self == required
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Admin => write!(f, "Admin"),
+            Self::Moderator => write!(f, "Moderator"),
+        }
+    }
+}
+
+impl Default for ContractConfig {
+    fn default() -> Self {
+        Self {
+            task_max_title_length: task::MAX_TITLE_LENGTH,
+            task_max_description_length: task::MAX_DESCRIPTION_LENGTH,
+            reward_max_title_length: reward::MAX_TITLE_LENGTH,
+            reward_max_description_length: reward::MAX_DESCRIPTION_LENGTH,
+            max_estimated_minutes: MAX_MINUTES,
+            max_future_time: MAX_FUTURE_TIME,
+            max_slot_future_time: MAX_SLOT_FUTURE_TIME,
+            task_max_storage: TASK_MAX_STORAGE,
+            reward_max_storage: REWARD_MAX_STORAGE,
+            time_slot_max_storage: TIME_SLOT_MAX_STORAGE,
+            habit_max_storage: HABIT_MAX_STORAGE,
+            max_subtask_depth: task::MAX_SUBTASK_DEPTH,
+            default_page_limit: DEFAULT_PAGE_LIMIT,
+            max_page_limit: MAX_PAGE_LIMIT,
+            min_chunk_minutes: MIN_CHUNK_MINUTES,
+            account_min_storage_bytes: ACCOUNT_MIN_STORAGE_BYTES,
+            keeper_fee_per_task: U128(KEEPER_FEE_PER_TASK),
+            streak_freeze_cost: STREAK_FREEZE_COST,
+            max_streak_freezes: MAX_STREAK_FREEZES,
+        }
+    }
+}
+
+impl ContractConfig {
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        let limits = [
+            ("task_max_title_length", self.task_max_title_length as u64),
+            ("task_max_description_length", self.task_max_description_length as u64),
+            ("reward_max_title_length", self.reward_max_title_length as u64),
+            ("reward_max_description_length", self.reward_max_description_length as u64),
+            ("max_estimated_minutes", self.max_estimated_minutes as u64),
+            ("max_future_time", self.max_future_time),
+            ("max_slot_future_time", self.max_slot_future_time),
+            ("task_max_storage", self.task_max_storage),
+            ("reward_max_storage", self.reward_max_storage),
+            ("time_slot_max_storage", self.time_slot_max_storage),
+            ("habit_max_storage", self.habit_max_storage),
+            ("streak_freeze_cost", self.streak_freeze_cost as u64),
+            ("max_streak_freezes", self.max_streak_freezes as u64),
+        ];
+        if let Some((limit, _)) = limits.iter().find(|(_, value)| *value == 0) {
+            return Err(ConfigValidationError::ZeroLimit { limit: limit.to_string() });
+        }
+
+        // Both windows are added to the block timestamp
+        let upper_bounds = [
+            ("max_future_time", self.max_future_time, MAX_FUTURE_TIME_LIMIT),
+            ("max_slot_future_time", self.max_slot_future_time, MAX_SLOT_FUTURE_TIME_LIMIT),
+            ("max_streak_freezes", self.max_streak_freezes as u64, MAX_STREAK_FREEZES_LIMIT as u64),
+        ];
+        if let Some((limit, _, max)) = upper_bounds.iter().find(|(_, value, max)| value > max) {
+            return Err(ConfigValidationError::LimitTooLarge { limit: limit.to_string(), max: *max });
+        }
+
+        if self.max_subtask_depth == 0 {
+            return Err(ConfigValidationError::SubtaskDepth {
+                provided_depth: self.max_subtask_depth,
+            });
+        }
+
+        if self.default_page_limit == 0 || self.default_page_limit > self.max_page_limit {
+            return Err(ConfigValidationError::PageLimit {
+                default_limit: self.default_page_limit,
+                max_limit: self.max_page_limit,
+            });
+        }
+
+        if self.min_chunk_minutes == 0 {
+            return Err(ConfigValidationError::ChunkMinutes {
+                provided_minutes: self.min_chunk_minutes,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_bounds_the_future_windows() {
+        assert_eq!(ContractConfig::default().validate(), Ok(()));
+
+        let config = ContractConfig { max_future_time: u64::MAX, ..ContractConfig::default() };
+        assert_eq!(config.validate(), Err(ConfigValidationError::LimitTooLarge {
+            limit: "max_future_time".to_string(),
+            max: MAX_FUTURE_TIME_LIMIT,
+        }));
+
+        let config = ContractConfig { max_slot_future_time: MAX_SLOT_FUTURE_TIME_LIMIT + 1, ..ContractConfig::default() };
+        assert_eq!(config.validate(), Err(ConfigValidationError::LimitTooLarge {
+            limit: "max_slot_future_time".to_string(),
+            max: MAX_SLOT_FUTURE_TIME_LIMIT,
+        }));
+    }
+
+    #[test]
+    fn validate_bounds_the_streak_freeze_cap() {
+        let config = ContractConfig { max_streak_freezes: 0, ..ContractConfig::default() };
+        assert_eq!(config.validate(), Err(ConfigValidationError::ZeroLimit {
+            limit: "max_streak_freezes".to_string(),
+        }));
+
+        let config = ContractConfig { max_streak_freezes: MAX_STREAK_FREEZES_LIMIT + 1, ..ContractConfig::default() };
+        assert_eq!(config.validate(), Err(ConfigValidationError::LimitTooLarge {
+            limit: "max_streak_freezes".to_string(),
+            max: MAX_STREAK_FREEZES_LIMIT as u64,
+        }));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::models::config::time::*;
+use crate::models::time_slot::DayOfWeek;
+
+// 1970-01-01 (day 0) was a Thursday
+const DAY_MAPPING: [DayOfWeek; 7] = [
+    DayOfWeek::Monday, DayOfWeek::Tuesday, DayOfWeek::Wednesday,
+    DayOfWeek::Thursday, DayOfWeek::Friday, DayOfWeek::Saturday, DayOfWeek::Sunday
+];
+
+/// Number of whole local days elapsed since the Unix epoch.
+pub fn day_index(timestamp: u64, utc_offset_minutes: i32) -> u64 {
+    let local_time = timestamp as i128 + utc_offset_minutes as i128 * NANOS_PER_MINUTE as i128;
+    local_time.max(0) as u64 / NANOS_PER_DAY
+}
+
+/// Timestamp of local midnight at the start of the given day.
+pub fn day_start(day: u64, utc_offset_minutes: i32) -> u64 {
+    let utc_time = (day * NANOS_PER_DAY) as i128 - utc_offset_minutes as i128 * NANOS_PER_MINUTE as i128;
+    utc_time.max(0) as u64
+}
+
+pub fn day_of_week(day: u64) -> DayOfWeek {
+    DAY_MAPPING[((day + 3) % 7) as usize].clone()
+}
+
+pub fn weekday_index(day: i64) -> i64 {
+    (day + 3).rem_euclid(7)
+}
+
+/// Day index of a proleptic Gregorian date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// (year, month, day) of a day index.
+pub fn civil_from_days(day: i64) -> (i64, u32, u32) {
+    let shifted = day + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day_of_month = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day_of_month)
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
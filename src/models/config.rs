@@ -1,28 +1,96 @@
 // === Storage Constants ===
 pub mod storage {
-    pub const REWARD_BASE_STORAGE: u64 = 128;
     pub const REWARD_MAX_STORAGE: u64 = 2048;
-    pub const TASK_BASE_STORAGE: u64 = 256;
     pub const TASK_MAX_STORAGE: u64 = 4096;
-    pub const TIME_SLOT_BASE_STORAGE: u64 = 128;
     pub const TIME_SLOT_MAX_STORAGE: u64 = 2048;
+    pub const HABIT_MAX_STORAGE: u64 = 2048;
+    pub const ACCOUNT_MIN_STORAGE_BYTES: u64 = 512;
+    // Charged by the protocol for every trie record on top of its key and value
+    pub const RECORD_OVERHEAD_BYTES: u64 = 40;
+    // Paid to overdue sweepers per task from the owner's deposit; zero turns it off
+    pub const KEEPER_FEE_PER_TASK: u128 = 0;
+}
+
+// === Collection Prefixes ===
+pub mod prefix {
+    pub const TASKS: &str = "t";
+    pub const TASKS_PER_OWNER: &str = "to";
+    pub const TASKS_BY_STATE: &str = "tbs";
+    pub const TASK_COMPLETIONS: &str = "tc";
+    pub const TASK_BLOCKERS: &str = "tb";
+    pub const TASK_DEPENDENTS: &str = "td";
+    pub const HABITS: &str = "h";
+    pub const HABITS_PER_OWNER: &str = "ho";
+    pub const HABIT_BY_TASK: &str = "ht";
+    pub const REWARDS: &str = "r";
+    pub const REWARDS_PER_OWNER: &str = "ro";
+    pub const REWARD_POINTS: &str = "rp";
+    pub const TIME_SLOTS: &str = "ts";
+    pub const TIME_SLOTS_PER_OWNER: &str = "tso";
+    pub const ACCOUNT_SETTINGS: &str = "as";
+    pub const ID_NONCES: &str = "in";
+    pub const STORAGE_ACCOUNTS: &str = "sa";
+    pub const ROLES: &str = "rl";
+    pub const POINTS_LEDGER: &str = "pl";
+    pub const PENALTY_POLICIES: &str = "pp";
+    pub const HABIT_HISTORY: &str = "hh";
+    pub const STREAK_FREEZES: &str = "sf";
 }
 
 // === Time Related Constants ===
 pub mod time {
+    pub const NANOS_PER_MINUTE: u64 = 60 * 1_000_000_000;
+    pub const NANOS_PER_DAY: u64 = 24 * 60 * NANOS_PER_MINUTE;
+    pub const MIN_UTC_OFFSET_MINUTES: i32 = -12 * 60;
+    pub const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
     pub const MAX_MINUTES: u32 = 24 * 60;
     pub const MAX_FUTURE_TIME: u64 = 365 * 24 * 60 * 60 * 1_000_000_000;
     pub const MAX_SLOT_FUTURE_TIME: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+    // Highest values the admin can configure for the two windows above
+    pub const MAX_FUTURE_TIME_LIMIT: u64 = 10 * MAX_FUTURE_TIME;
+    pub const MAX_SLOT_FUTURE_TIME_LIMIT: u64 = MAX_FUTURE_TIME;
+}
+
+// === Recurrence Related Constants ===
+pub mod recurrence {
+    pub const MAX_INTERVAL: u32 = 1000;
+    pub const MAX_OCCURRENCE_COUNT: u32 = 1000;
+    // Bounds the search for the next occurrence of sparse rules such as Feb 29
+    pub const MAX_PERIODS_SCANNED: u64 = 5000;
+    pub const MAX_STATS_DAYS: u64 = 366;
+    pub const MAX_STATS_PERIODS: u32 = 366;
+}
+
+// === Habit Related Constants ===
+pub mod habit {
+    // A day at most, so a late completion can't reach past the following occurrence
+    pub const MAX_GRACE_MINUTES: u32 = 24 * 60;
+    pub const STREAK_FREEZE_COST: u32 = 50;
+    pub const MAX_STREAK_FREEZES: u32 = 5;
+    // Highest value the admin can configure for the one above
+    pub const MAX_STREAK_FREEZES_LIMIT: u32 = 100;
 }
 
 // === Task Related Constants ===
 pub mod task {
-    pub const MAX_TITLE_LENGTH: usize = 256;
-    pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+    pub const MAX_TITLE_LENGTH: u32 = 256;
+    pub const MAX_DESCRIPTION_LENGTH: u32 = 1024;
+    pub const MAX_SUBTASK_DEPTH: u32 = 5;
 }
 
 // === Reward Related Constants ===
 pub mod reward {
-    pub const MAX_TITLE_LENGTH: usize = 256;
-    pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+    pub const MAX_TITLE_LENGTH: u32 = 256;
+    pub const MAX_DESCRIPTION_LENGTH: u32 = 1024;
+}
+
+// === Query Related Constants ===
+pub mod query {
+    pub const DEFAULT_PAGE_LIMIT: u64 = 20;
+    pub const MAX_PAGE_LIMIT: u64 = 100;
+}
+
+// === Scheduling Related Constants ===
+pub mod schedule {
+    pub const MIN_CHUNK_MINUTES: u32 = 15;
 }
\ No newline at end of file
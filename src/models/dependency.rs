@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+use crate::models::task::{Task, TaskId};
+
+/// Returns true if `target` can be reached from `start` by following blocker edges.
+pub fn has_path(start: &TaskId, target: &TaskId, blockers_of: impl Fn(&TaskId) -> Vec<TaskId>) -> bool {
+    let mut visited: HashSet<TaskId> = HashSet::new();
+    let mut stack = vec![start.clone()];
+
+    while let Some(current) = stack.pop() {
+        if &current == target {
+            return true;
+        }
+        if !visited.insert(current.clone()) {
+            continue;
+        }
+        stack.extend(blockers_of(&current));
+    }
+    false
+}
+
+/// Orders tasks so every task comes after its blockers (Kahn's algorithm).
+/// Among tasks that are ready at the same time, higher priority and earlier
+/// deadlines come first. Blockers outside `tasks` are ignored.
+pub fn topological_order(tasks: Vec<Task>, blockers: &HashMap<TaskId, Vec<TaskId>>) -> Vec<Task> {
+    let ids: HashSet<TaskId> = tasks.iter().map(|task| task.id.clone()).collect();
+    let mut pending: HashMap<TaskId, usize> = HashMap::new();
+    let mut dependents: HashMap<TaskId, Vec<TaskId>> = HashMap::new();
+
+    for task in &tasks {
+        let task_blockers: Vec<&TaskId> = blockers
+            .get(&task.id)
+            .map(|list| list.iter().filter(|id| ids.contains(*id)).collect())
+            .unwrap_or_default();
+        pending.insert(task.id.clone(), task_blockers.len());
+        for blocker_id in task_blockers {
+            dependents.entry(blocker_id.clone()).or_default().push(task.id.clone());
+        }
+    }
+
+    let mut remaining: HashMap<TaskId, Task> = tasks
+        .into_iter()
+        .map(|task| (task.id.clone(), task))
+        .collect();
+    let mut ordered = Vec::with_capacity(remaining.len());
+
+    loop {
+        let next_id = remaining
+            .values()
+            .filter(|task| pending.get(&task.id) == Some(&0))
+            .min_by(|a, b| {
+                b.priority.cmp(&a.priority)
+                    .then(a.deadline.cmp(&b.deadline))
+                    .then(a.id.cmp(&b.id))
+            })
+            .map(|task| task.id.clone());
+
+        let Some(next_id) = next_id else { break };
+        let task = remaining.remove(&next_id).unwrap();
+
+        for dependent_id in dependents.get(&next_id).into_iter().flatten() {
+            if let Some(count) = pending.get_mut(dependent_id) {
+                *count -= 1;
+            }
+        }
+        ordered.push(task);
+    }
+
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+    use crate::models::admin::ContractConfig;
+    use crate::models::task::Priority;
+
+    const NOW: u64 = 1_700_000_000_000_000_000;
+    const HOUR: u64 = 3_600_000_000_000;
+
+    fn task(id: &str, priority: Priority, deadline_hours: u64) -> Task {
+        testing_env!(VMContextBuilder::new().block_timestamp(NOW).build());
+        Task::new(
+            id.to_string(),
+            id.to_string(),
+            String::new(),
+            priority,
+            NOW + deadline_hours * HOUR,
+            30,
+            Vec::new(),
+            "alice.near".parse().unwrap(),
+            &ContractConfig::default(),
+        ).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn graph(edges: &[(&str, &str)]) -> HashMap<TaskId, Vec<TaskId>> {
+        let mut blockers: HashMap<TaskId, Vec<TaskId>> = HashMap::new();
+        for (task_id, blocker_id) in edges {
+            blockers.entry(task_id.to_string()).or_default().push(blocker_id.to_string());
+        }
+        blockers
+    }
+
+    fn order(tasks: Vec<Task>, blockers: &HashMap<TaskId, Vec<TaskId>>) -> Vec<TaskId> {
+        topological_order(tasks, blockers).into_iter().map(|task| task.id).collect()
+    }
+
+    #[test]
+    fn has_path_follows_blockers_transitively() {
+        // c waits on b, which waits on a
+        let blockers = graph(&[("c", "b"), ("b", "a"), ("d", "a")]);
+        let blockers_of = |id: &TaskId| blockers.get(id).cloned().unwrap_or_default();
+
+        // So making a wait on c would close a cycle, while the reverse is fine
+        assert!(has_path(&"c".to_string(), &"a".to_string(), blockers_of));
+        assert!(!has_path(&"a".to_string(), &"c".to_string(), blockers_of));
+        assert!(!has_path(&"d".to_string(), &"c".to_string(), blockers_of));
+    }
+
+    #[test]
+    fn has_path_terminates_on_existing_cycles() {
+        let blockers = graph(&[("a", "b"), ("b", "a")]);
+        let blockers_of = |id: &TaskId| blockers.get(id).cloned().unwrap_or_default();
+        assert!(!has_path(&"a".to_string(), &"c".to_string(), blockers_of));
+    }
+
+    #[test]
+    fn blockers_come_before_the_tasks_waiting_on_them() {
+        let tasks = vec![
+            task("write", Priority::Critical, 1),
+            task("research", Priority::Low, 10),
+            task("review", Priority::High, 2),
+        ];
+        let blockers = graph(&[("write", "research"), ("review", "write")]);
+        assert_eq!(order(tasks, &blockers), ["research", "write", "review"]);
+    }
+
+    #[test]
+    fn ready_tasks_are_ordered_by_priority_then_deadline() {
+        let tasks = vec![
+            task("low", Priority::Low, 1),
+            task("high-late", Priority::High, 9),
+            task("high-early", Priority::High, 3),
+            task("after-low", Priority::Critical, 1),
+        ];
+        // Blockers outside the list are ignored
+        let blockers = graph(&[("after-low", "low"), ("high-late", "elsewhere")]);
+        assert_eq!(order(tasks, &blockers), ["high-early", "high-late", "low", "after-low"]);
+    }
+
+    #[test]
+    fn tasks_on_a_cycle_are_left_out() {
+        let tasks = vec![task("a", Priority::Low, 1), task("b", Priority::Low, 2), task("c", Priority::Low, 3)];
+        let blockers = graph(&[("a", "b"), ("b", "a")]);
+        assert_eq!(order(tasks, &blockers), ["c"]);
+    }
+}
@@ -1,15 +1,82 @@
 use schemars::JsonSchema;
+use std::collections::{HashMap, HashSet};
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
     env, AccountId};
 use crate::models::traits::{
-    Storable, StorageError, StorageMetrics, Ownable};
-use crate::models::time_slot::{RecurrencePattern, Frequency, DayOfWeek};
+    Storable, StorageError, Ownable};
+use crate::models::recurrence::{RecurrencePattern, RecurrencePatternV0, RecurrenceError};
 use crate::models::task::TaskId;
+use crate::models::calendar::{day_index, day_start};
+use crate::models::config::{time::*, habit::MAX_GRACE_MINUTES, prefix};
+use crate::models::admin::ContractConfig;
+use crate::models::storage::{borsh_len, record_bytes};
 
 pub type HabitId = String;
 
+// === Error Hierarchy ===
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum HabitError {
+    Validation(HabitValidationError),
+    Storage(StorageError),
+    State(HabitStateError),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum HabitValidationError {
+    Recurrence(RecurrenceError),
+    Grace { provided_minutes: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum HabitStateError {
+    AlreadyPaused,
+    NotPaused,
+}
+
+impl From<StorageError> for HabitError {
+    fn from(err: StorageError) -> Self {
+        HabitError::Storage(err)
+    }
+}
+
+impl From<HabitValidationError> for HabitError {
+    fn from(err: HabitValidationError) -> Self {
+        HabitError::Validation(err)
+    }
+}
+
+impl From<HabitStateError> for HabitError {
+    fn from(err: HabitStateError) -> Self {
+        HabitError::State(err)
+    }
+}
+
+impl std::fmt::Display for HabitValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Recurrence(reason) => write!(f, "Recurrence error: {}", reason),
+            Self::Grace { provided_minutes } => {
+                write!(f, "Grace window must be at most {} minutes (minutes: {})", MAX_GRACE_MINUTES, provided_minutes)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for HabitStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AlreadyPaused => write!(f, "Habit is already paused"),
+            Self::NotPaused => write!(f, "Habit is not paused"),
+        }
+    }
+}
+
+// === Core Data Structures ===
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Habit {
@@ -20,27 +87,159 @@ pub struct Habit {
     pub last_completed: u64,
     #[schemars(with = "String")]
     owner_id: AccountId,
+    // Paused habits keep their streak but don't roll their task forward
+    pub paused: bool,
+    // Occurrence day the next completion has to count towards to extend the streak
+    pub next_due_day: Option<u64>,
+    // How long after its day an occurrence can still be completed without breaking the streak
+    pub grace_minutes: u32,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema,
+    Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum HabitHistoryKind {
+    Completed,
+    // Completed within the grace window after the occurrence's day
+    CompletedLate,
+    // A streak freeze covered the missed occurrence
+    FreezeUsed,
+    StreakReset,
+}
+
+// One append-only history record; `streak` is the streak after it applied
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema,
+    Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HabitHistoryEntry {
+    pub kind: HabitHistoryKind,
+    pub occurrence_day: u64,
+    pub timestamp: u64,
+    pub streak: u32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HabitHistoryPage {
+    pub entries: Vec<HabitHistoryEntry>,
+    pub total: u64,
+}
+
+// One day of a completion heatmap, by local day index
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HabitDay {
+    pub day: u64,
+    pub scheduled: bool,
+    pub completions: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HabitStats {
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    // The most recent occurrences that are completed or already passed, up to the requested count
+    pub periods: u32,
+    pub completed_periods: u32,
+    pub completion_rate_percent: u8,
+    pub days: Vec<HabitDay>,
+}
+
+// Layout deployed before versioning
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct HabitV0 {
+    id: HabitId,
+    task_id: TaskId,
+    recurrence: RecurrencePatternV0,
+    streak: u32,
+    last_completed: u64,
+    owner_id: AccountId,
 }
 
+impl From<HabitV0> for Habit {
+    fn from(old: HabitV0) -> Self {
+        Self {
+            id: old.id,
+            task_id: old.task_id,
+            recurrence: old.recurrence.into(),
+            streak: old.streak,
+            last_completed: old.last_completed,
+            owner_id: old.owner_id,
+            paused: false,
+            next_due_day: None,
+            grace_minutes: 0,
+        }
+    }
+}
+
+// === Core Implementations ===
 impl Habit {
     pub fn new(
+        id: HabitId,
         task_id: TaskId,
         recurrence: RecurrencePattern,
         owner_id: AccountId,
-    ) -> Result<Self, StorageError> {
+        config: &ContractConfig,
+    ) -> Result<Self, HabitError> {
         let mut habit = Self {
-            id: format!("habit-{}-{}", owner_id, env::block_timestamp()),
+            id,
             task_id,
-            recurrence,
+            recurrence: recurrence.anchored(env::block_timestamp()),
             owner_id,
             streak: 0,
             last_completed: 0,
+            paused: false,
+            next_due_day: None,
+            grace_minutes: 0,
         };
         
-        habit.validate_storage()?;
+        habit.validate(config)?;
         Ok(habit)
     }
 
+    pub fn validate(&mut self, config: &ContractConfig) -> Result<(), HabitError> {
+        self.validate_recurrence()?;
+        self.validate_grace()?;
+        self.validate_storage(config)?;
+        Ok(())
+    }
+
+    pub fn validate_recurrence(&self) -> Result<(), HabitValidationError> {
+        self.recurrence.validate().map_err(HabitValidationError::Recurrence)
+    }
+
+    pub fn validate_grace(&self) -> Result<(), HabitValidationError> {
+        if self.grace_minutes > MAX_GRACE_MINUTES {
+            return Err(HabitValidationError::Grace { provided_minutes: self.grace_minutes });
+        }
+        Ok(())
+    }
+
+    pub fn pause(&mut self) -> Result<(), HabitStateError> {
+        if self.paused {
+            return Err(HabitStateError::AlreadyPaused);
+        }
+        self.paused = true;
+        Ok(())
+    }
+
+    // Occurrences missed while paused don't break the streak
+    pub fn resume(&mut self, current_time: u64, utc_offset_minutes: i32) -> Result<(), HabitStateError> {
+        if !self.paused {
+            return Err(HabitStateError::NotPaused);
+        }
+        self.paused = false;
+        self.rebase(current_time, utc_offset_minutes);
+        Ok(())
+    }
+
+    // Expects the first occurrence from today onwards, forgiving anything missed before it
+    pub fn rebase(&mut self, current_time: u64, utc_offset_minutes: i32) {
+        let current_day = day_index(current_time, utc_offset_minutes);
+        self.next_due_day = self.recurrence.first_occurrence_day(current_day, utc_offset_minutes);
+    }
+
     pub fn increment_streak(&mut self) -> u32 {
         self.streak += 1;
         self.last_completed = env::block_timestamp();
@@ -52,38 +251,187 @@ impl Habit {
         self.last_completed = env::block_timestamp();
     }
 
-    pub fn verify_streak_continuity(&self) -> bool {
-        if self.last_completed == 0 {
-            return true;
-        }
-        
+    // Habits stored before next_due_day expect the occurrence after their last completion
+    fn expected_occurrence(&self, utc_offset_minutes: i32) -> Option<u64> {
+        self.next_due_day.or_else(|| {
+            if self.last_completed == 0 {
+                return None;
+            }
+            let last_completed_day = day_index(self.last_completed, utc_offset_minutes);
+            self.recurrence.next_occurrence_day(last_completed_day, utc_offset_minutes)
+        })
+    }
+
+    fn within_grace(&self, occurrence_day: u64, current_time: u64, utc_offset_minutes: i32) -> bool {
+        let grace_end = day_start(occurrence_day + 1, utc_offset_minutes)
+            + self.grace_minutes as u64 * NANOS_PER_MINUTE;
+        current_time < grace_end
+    }
+
+    // A completion counts towards the first occurrence on or after its day, so completing
+    // early is fine but any occurrence passing uncompleted, grace window included, breaks
+    // the streak. Streak freezes aren't taken into account
+    pub fn verify_streak_continuity(&self, utc_offset_minutes: i32) -> bool {
         let current_time = env::block_timestamp();
-        let time_diff = current_time - self.last_completed;
-        
-        match &self.recurrence.frequency {
-            Frequency::Daily => {
-                let interval = self.recurrence.interval.unwrap_or(1);
-                let allowed_time = (interval as u64) * 24 * 60 * 60 * 1_000_000_000;
-                time_diff <= allowed_time
+        let current_day = day_index(current_time, utc_offset_minutes);
+        match (
+            self.recurrence.first_occurrence_day(current_day, utc_offset_minutes),
+            self.expected_occurrence(utc_offset_minutes),
+        ) {
+            (Some(due_day), Some(expected_day)) => {
+                due_day <= expected_day || self.within_grace(expected_day, current_time, utc_offset_minutes)
             },
-            Frequency::Custom => {
-                if let Some(ref days) = self.recurrence.specific_days {
-                    let seconds_per_day = 24 * 60 * 60;
-                    let last_completed_days = (self.last_completed / 1_000_000_000) / seconds_per_day;
-                    let current_days = (current_time / 1_000_000_000) / seconds_per_day;
-                    
-                    let current_day_of_week = ((current_days + 3) % 7) as usize;
-                    
-                    let day_mapping = [
-                        DayOfWeek::Monday, DayOfWeek::Tuesday, DayOfWeek::Wednesday,
-                        DayOfWeek::Thursday, DayOfWeek::Friday, DayOfWeek::Saturday, DayOfWeek::Sunday
-                    ];
-                    
-                    days.contains(&day_mapping[current_day_of_week]) && (current_days - last_completed_days) <= 7
-                } else {
-                    false
+            _ => true,
+        }
+    }
+
+    // Each occurrence extends the streak at most once, however often it is completed.
+    // A late completion within the grace window still counts for the missed occurrence;
+    // otherwise missed occurrences are covered by one freeze each when enough are
+    // available. The returned entries say which freezes were used
+    pub fn record_completion(
+        &mut self,
+        current_time: u64,
+        utc_offset_minutes: i32,
+        available_freezes: u32,
+    ) -> Vec<HabitHistoryEntry> {
+        let mut entries = Vec::new();
+        let current_day = day_index(current_time, utc_offset_minutes);
+        if let Some(mut due_day) = self.recurrence.first_occurrence_day(current_day, utc_offset_minutes) {
+            let mut kind = HabitHistoryKind::Completed;
+            match self.expected_occurrence(utc_offset_minutes) {
+                Some(expected_day) if due_day < expected_day => (),
+                Some(expected_day) if due_day > expected_day => {
+                    if self.within_grace(expected_day, current_time, utc_offset_minutes) {
+                        due_day = expected_day;
+                        kind = HabitHistoryKind::CompletedLate;
+                    } else {
+                        let missed = self.recurrence.occurrence_days(expected_day, due_day - 1, utc_offset_minutes);
+                        if missed.len() as u64 <= available_freezes as u64 {
+                            for day in missed {
+                                entries.push(self.history_entry(HabitHistoryKind::FreezeUsed, day, current_time));
+                            }
+                        } else {
+                            self.reset_streak();
+                            entries.push(self.history_entry(HabitHistoryKind::StreakReset, expected_day, current_time));
+                        }
+                    }
+                    self.increment_streak();
+                },
+                _ => {
+                    self.increment_streak();
                 }
             }
+            // Past the end of a finished series, so it isn't counted again
+            self.next_due_day = Some(self.recurrence
+                .next_occurrence_day(due_day, utc_offset_minutes)
+                .unwrap_or(due_day + 1));
+            entries.push(self.history_entry(kind, due_day, current_time));
+        }
+        self.last_completed = current_time;
+        entries
+    }
+
+    fn history_entry(&self, kind: HabitHistoryKind, occurrence_day: u64, timestamp: u64) -> HabitHistoryEntry {
+        HabitHistoryEntry {
+            kind,
+            occurrence_day,
+            timestamp,
+            streak: self.streak,
+        }
+    }
+
+    // Same time of day on the next occurrence still to be completed, skipping any already
+    // counted by an early completion, or the end of today while today's occurrence is still
+    // open after a late completion or a resume; None once the recurrence has ended
+    pub fn next_deadline(&self, current_time: u64, utc_offset_minutes: i32) -> Option<u64> {
+        let current_day = day_index(current_time, utc_offset_minutes);
+        let from_day = self.next_due_day.map_or(current_day + 1, |due_day| due_day.max(current_day));
+        self.recurrence
+            .first_occurrence_day(from_day, utc_offset_minutes)
+            .map(|next_day| if next_day == current_day {
+                day_start(current_day + 1, utc_offset_minutes)
+            } else {
+                current_time + (next_day - current_day) * NANOS_PER_DAY
+            })
+    }
+
+    // Replays the completion history against the occurrences with the same rule as
+    // record_completion; completions are expected in chronological order
+    pub fn stats(
+        &self,
+        completions: &[u64],
+        periods: u32,
+        from_day: u64,
+        to_day: u64,
+        utc_offset_minutes: i32,
+    ) -> HabitStats {
+        let current_day = day_index(env::block_timestamp(), utc_offset_minutes);
+        let completion_days: Vec<u64> = completions.iter()
+            .map(|&timestamp| day_index(timestamp, utc_offset_minutes))
+            .collect();
+
+        let first_day = self.recurrence.start
+            .map(|start| day_index(start, utc_offset_minutes))
+            .or(completion_days.first().copied())
+            .unwrap_or(current_day);
+        let last_day = self.recurrence
+            .first_occurrence_day(current_day, utc_offset_minutes)
+            .unwrap_or(current_day);
+        let occurrences = self.recurrence.occurrence_days(first_day, last_day, utc_offset_minutes);
+
+        let completed: HashSet<u64> = completion_days.iter()
+            .filter_map(|&day| occurrences.get(occurrences.partition_point(|&occurrence| occurrence < day)))
+            .copied()
+            .collect();
+        let settled: Vec<bool> = occurrences.iter()
+            .map(|occurrence| (*occurrence, completed.contains(occurrence)))
+            .filter(|&(occurrence, done)| done || occurrence < current_day)
+            .map(|(_, done)| done)
+            .collect();
+
+        // Pauses and recurrence updates forgive misses, so the live streak can beat the replay
+        let mut run = 0;
+        let mut longest_streak = self.streak;
+        for &done in &settled {
+            run = if done { run + 1 } else { 0 };
+            longest_streak = longest_streak.max(run);
+        }
+
+        let recent = &settled[settled.len().saturating_sub(periods as usize)..];
+        let completed_periods = recent.iter().filter(|&&done| done).count() as u32;
+        let completion_rate_percent = if recent.is_empty() {
+            0
+        } else {
+            (completed_periods as u64 * 100 / recent.len() as u64) as u8
+        };
+
+        let scheduled: HashSet<u64> = self.recurrence
+            .occurrence_days(from_day, to_day, utc_offset_minutes)
+            .into_iter()
+            .collect();
+        let mut completions_per_day: HashMap<u64, u32> = HashMap::new();
+        for &day in &completion_days {
+            *completions_per_day.entry(day).or_default() += 1;
+        }
+        let days = (from_day..=to_day)
+            .map(|day| HabitDay {
+                day,
+                scheduled: scheduled.contains(&day),
+                completions: completions_per_day.get(&day).copied().unwrap_or(0),
+            })
+            .collect();
+
+        // The stored streak only resets on the next completion
+        let current_streak = if self.verify_streak_continuity(utc_offset_minutes) { self.streak } else { 0 };
+
+        HabitStats {
+            current_streak,
+            longest_streak,
+            periods: recent.len() as u32,
+            completed_periods,
+            completion_rate_percent,
+            days,
         }
     }
 }
@@ -95,30 +443,210 @@ impl Ownable for Habit {
 }
 
 impl Storable for Habit {
-    const BASE_STORAGE: u64 = 128;
-    const MAX_STORAGE: u64 = 2048;
-    
-    fn calculate_storage_metrics(&self) -> StorageMetrics {
-        let dynamic_size = 
-            self.id.len() as u64 +
-            self.task_id.len() as u64 +
-            self.owner_id.to_string().len() as u64 +
-            match &self.recurrence {
-                RecurrencePattern { specific_days: Some(days), .. } => {
-                    days.len() as u64 * std::mem::size_of::<DayOfWeek>() as u64
-                },
-                _ => 0,
-            };
-            
-        let total_bytes = Self::BASE_STORAGE + dynamic_size;
-        let cost_per_byte = env::storage_byte_cost().as_yoctonear();
-        let metrics = StorageMetrics {
-            base_size: Self::BASE_STORAGE,
-            dynamic_size,
-            total_bytes,
-            cost_per_byte,
-            total_cost: cost_per_byte * total_bytes as u128,
-        };
-        metrics
+    const MAP_PREFIX: &'static str = prefix::HABITS;
+    const OWNER_INDEX_PREFIX: &'static str = prefix::HABITS_PER_OWNER;
+
+    fn storage_key(&self) -> &str {
+        &self.id
+    }
+
+    fn max_storage(config: &ContractConfig) -> u64 {
+        config.habit_max_storage
+    }
+
+    fn index_bytes(&self) -> u64 {
+        record_bytes(
+            prefix::HABIT_BY_TASK.len() as u64 + borsh_len(&self.task_id),
+            borsh_len(&self.id),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+    use crate::models::recurrence::Frequency;
+    use crate::models::time_slot::DayOfWeek;
+
+    const START_DAY: u64 = 20_000;
+    const MAX_TESTED_INTERVAL: u32 = 4;
+    const DAYS: [DayOfWeek; 7] = [
+        DayOfWeek::Monday, DayOfWeek::Tuesday, DayOfWeek::Wednesday, DayOfWeek::Thursday,
+        DayOfWeek::Friday, DayOfWeek::Saturday, DayOfWeek::Sunday,
+    ];
+
+    fn at(day: u64, hour: u64) -> u64 {
+        day_start(day, 0) + hour * 60 * NANOS_PER_MINUTE
+    }
+
+    fn set_time(timestamp: u64) {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+    }
+
+    // Every Daily interval, and every weekday at every interval for Custom and Weekly
+    fn patterns() -> Vec<(String, RecurrencePattern)> {
+        let mut patterns = Vec::new();
+        for interval in 1..=MAX_TESTED_INTERVAL {
+            patterns.push((
+                format!("Daily every {}", interval),
+                RecurrencePattern { interval: Some(interval), ..RecurrencePattern::new_daily() },
+            ));
+            for day in DAYS {
+                for frequency in [Frequency::Custom, Frequency::Weekly] {
+                    patterns.push((
+                        format!("{:?} on {:?} every {}", frequency, day, interval),
+                        RecurrencePattern {
+                            frequency: frequency.clone(),
+                            interval: Some(interval),
+                            ..RecurrencePattern::new_custom(vec![day.clone()])
+                        },
+                    ));
+                }
+            }
+        }
+        patterns
+    }
+
+    // The habit, created at the start of START_DAY, with its first four occurrences
+    fn habit(recurrence: RecurrencePattern) -> (Habit, Vec<u64>) {
+        set_time(at(START_DAY, 0));
+        let habit = Habit::new(
+            "habit-alice.near-1".to_string(),
+            "task-alice.near-1".to_string(),
+            recurrence,
+            "alice.near".parse().unwrap(),
+            &ContractConfig::default(),
+        ).unwrap_or_else(|_| panic!("invalid habit"));
+        let occurrences: Vec<u64> = habit.recurrence
+            .occurrence_days(START_DAY, START_DAY + 200, 0)
+            .into_iter()
+            .take(4)
+            .collect();
+        assert_eq!(occurrences.len(), 4);
+        (habit, occurrences)
+    }
+
+    fn complete(habit: &mut Habit, timestamp: u64) -> Vec<HabitHistoryEntry> {
+        set_time(timestamp);
+        habit.record_completion(timestamp, 0, 0)
+    }
+
+    fn continuous(habit: &Habit, timestamp: u64) -> bool {
+        set_time(timestamp);
+        habit.verify_streak_continuity(0)
+    }
+
+    fn resets(entries: &[HabitHistoryEntry]) -> bool {
+        entries.iter().any(|entry| entry.kind == HabitHistoryKind::StreakReset)
+    }
+
+    #[test]
+    fn skipping_a_scheduled_day_breaks_the_streak() {
+        for (name, recurrence) in patterns() {
+            let (mut habit, occurrences) = habit(recurrence);
+            complete(&mut habit, at(occurrences[0], 9));
+            complete(&mut habit, at(occurrences[1], 9));
+            assert_eq!(habit.streak, 2, "{}", name);
+
+            assert!(continuous(&habit, at(occurrences[2], 23)), "{}", name);
+            assert!(!continuous(&habit, at(occurrences[2] + 1, 9)), "{}", name);
+
+            let entries = complete(&mut habit, at(occurrences[3], 9));
+            assert!(resets(&entries), "{}", name);
+            assert_eq!(habit.streak, 1, "{}", name);
+        }
+    }
+
+    #[test]
+    fn completing_on_an_unscheduled_day_keeps_the_streak() {
+        for (name, recurrence) in patterns() {
+            let (mut habit, occurrences) = habit(recurrence);
+            // Daily every day has no unscheduled days
+            if occurrences[1] - occurrences[0] == 1 {
+                continue;
+            }
+            complete(&mut habit, at(occurrences[0], 9));
+
+            let unscheduled_day = occurrences[0] + 1;
+            assert!(!habit.recurrence.occurs_on_day(unscheduled_day, 0), "{}", name);
+            let entries = complete(&mut habit, at(unscheduled_day, 9));
+            assert!(!resets(&entries), "{}", name);
+            assert_eq!(habit.streak, 2, "{}", name);
+
+            assert!(continuous(&habit, at(occurrences[1], 23)), "{}", name);
+            let entries = complete(&mut habit, at(occurrences[2], 9));
+            assert!(!resets(&entries), "{}", name);
+            assert_eq!(habit.streak, 3, "{}", name);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn early_completions_in_one_period_count_once() {
+        for (name, recurrence) in patterns() {
+            let (mut habit, occurrences) = habit(recurrence);
+            for hour in [8, 12, 20] {
+                complete(&mut habit, at(occurrences[0], hour));
+            }
+            assert_eq!(habit.streak, 1, "{}", name);
+
+            // Everything up to and including the next occurrence counts for it once
+            for day in occurrences[0] + 1..=occurrences[1] {
+                for hour in [9, 18] {
+                    let entries = complete(&mut habit, at(day, hour));
+                    assert!(!resets(&entries), "{}", name);
+                }
+            }
+            assert_eq!(habit.streak, 2, "{}", name);
+
+            assert!(continuous(&habit, at(occurrences[2], 23)), "{}", name);
+            complete(&mut habit, at(occurrences[2], 9));
+            assert_eq!(habit.streak, 3, "{}", name);
+        }
+    }
+    #[test]
+    fn stats_replay_streaks_and_completion_rates() {
+        let (mut habit, _) = habit(RecurrencePattern::new_daily());
+        let mut completions = Vec::new();
+        // Day 3 is missed, and day 5 is completed twice
+        for day in [0, 1, 2, 4, 5, 5] {
+            let timestamp = at(START_DAY + day, 9 + completions.len() as u64);
+            complete(&mut habit, timestamp);
+            completions.push(timestamp);
+        }
+        assert_eq!(habit.streak, 2);
+
+        // Day 6 is today and still open, so it isn't settled yet
+        set_time(at(START_DAY + 6, 12));
+        let stats = habit.stats(&completions, 4, START_DAY + 3, START_DAY + 6, 0);
+        assert_eq!((stats.current_streak, stats.longest_streak), (2, 3));
+        assert_eq!((stats.periods, stats.completed_periods, stats.completion_rate_percent), (4, 3, 75));
+        let days: Vec<(u64, bool, u32)> = stats.days
+            .iter()
+            .map(|day| (day.day - START_DAY, day.scheduled, day.completions))
+            .collect();
+        assert_eq!(days, [(3, true, 0), (4, true, 1), (5, true, 2), (6, true, 0)]);
+
+        // More periods than settled occurrences covers the whole history
+        let stats = habit.stats(&completions, 30, START_DAY, START_DAY, 0);
+        assert_eq!((stats.periods, stats.completed_periods, stats.completion_rate_percent), (6, 5, 83));
+
+        // Missing days 6 and 7 ends the live streak but not the longest one
+        set_time(at(START_DAY + 8, 12));
+        let stats = habit.stats(&completions, 30, START_DAY, START_DAY, 0);
+        assert_eq!((stats.current_streak, stats.longest_streak), (0, 3));
+        assert_eq!((stats.periods, stats.completed_periods), (8, 5));
+    }
+
+    #[test]
+    fn stats_only_schedule_the_recurrence_days() {
+        let (habit, occurrences) = habit(RecurrencePattern::new_custom(vec![DayOfWeek::Wednesday]));
+        set_time(at(occurrences[0], 12));
+        let stats = habit.stats(&[], 1, occurrences[0] - 1, occurrences[0] + 7, 0);
+        assert_eq!((stats.periods, stats.completion_rate_percent), (0, 0));
+        let scheduled: Vec<u64> = stats.days.iter().filter(|day| day.scheduled).map(|day| day.day).collect();
+        assert_eq!(scheduled, [occurrences[0], occurrences[1]]);
+        assert_eq!(stats.days.len(), 9);
+    }
+}
@@ -3,20 +3,49 @@ pub mod reward;
 pub mod task;
 pub mod habit;
 pub mod time_slot;
+pub mod recurrence;
 pub mod config;
+pub mod calendar;
+pub mod schedule;
+pub mod settings;
+pub mod dependency;
+pub mod query;
+pub mod storage;
+pub mod versioned;
+pub mod admin;
+pub mod points;
 
 pub use config::*;
 
-pub use task::{Task, TaskId, Priority, TaskState, TaskTimeSlot,
+pub use task::{Task, TaskId, Priority, TaskState, TaskTimeSlot, TaskTreeNode, OverdueSweep,
     TaskError, TaskValidationError, TaskStateError};
 
-pub use habit::{Habit, HabitId};
+pub use habit::{Habit, HabitId, HabitStats, HabitDay,
+    HabitHistoryEntry, HabitHistoryKind, HabitHistoryPage,
+    HabitError, HabitValidationError, HabitStateError};
 
 pub use reward::{Reward, RewardId, RewardState,
     RewardError, RewardValidationError, RewardStateError};
 
-pub use time_slot::{TimeSlot, TimeSlotId, SlotType, RecurrencePattern,
-    Frequency, DayOfWeek, TimeSlotError, TimeSlotValidationError};
+pub use time_slot::{TimeSlot, TimeSlotId, SlotType, SlotOccurrence, DayOfWeek,
+    TimeSlotError, TimeSlotValidationError};
+
+pub use recurrence::{RecurrencePattern, Frequency, RecurrenceError};
+
+pub use schedule::{SchedulePlan, ScheduledTask, ScheduleConflict, ConflictSource};
+
+pub use settings::{AccountSettings, PenaltyPolicy, SettingsValidationError};
+
+pub use query::{TaskQuery, TaskPage, TaskSortKey};
+
+pub use storage::{StorageAccount, StorageBalance, StorageBalanceBounds,
+    StorageReport, EntityStorage};
     
+pub use admin::{Role, ContractConfig, ConfigValidationError};
+
+pub use points::{PointsEntry, PointsEntryKind, PointsSource, PointsHistoryPage, PointsError};
+
+pub use versioned::{VersionedTask, VersionedHabit, VersionedReward, VersionedTimeSlot};
+
 pub use traits::{Ownable, Storable, StorageError, 
                  StorageMetrics, OwnershipError};
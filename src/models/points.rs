@@ -0,0 +1,115 @@
+use schemars::JsonSchema;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize}};
+
+use crate::models::task::TaskId;
+use crate::models::reward::RewardId;
+
+// === Error Hierarchy ===
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema,
+    Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PointsError {
+    Overflow { balance: u32, amount: u32 },
+    InsufficientPoints { balance: u32, required: u32 },
+}
+
+impl std::fmt::Display for PointsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow { balance, amount } => {
+                write!(f, "Crediting {} points would overflow the balance of {}", amount, balance)
+            },
+            Self::InsufficientPoints { balance, required } => {
+                write!(f, "Insufficient points: has {}, needs {}", balance, required)
+            }
+        }
+    }
+}
+
+// === Core State and Action Enums ===
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema,
+    Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PointsEntryKind {
+    Credit,
+    Debit,
+}
+
+// What caused a balance change
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema,
+    Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PointsSource {
+    Task(TaskId),
+    Reward(RewardId),
+    StreakFreeze,
+}
+
+// === Core Data Structures ===
+// One append-only ledger record; `balance` is the account balance after it applied
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema,
+    Debug, PartialEq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PointsEntry {
+    pub kind: PointsEntryKind,
+    pub source: PointsSource,
+    pub amount: u32,
+    pub timestamp: u64,
+    pub balance: u32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PointsHistoryPage {
+    pub entries: Vec<PointsEntry>,
+    pub total: u64,
+}
+
+// === Core Implementations ===
+impl PointsEntryKind {
+    pub fn apply(self, balance: u32, amount: u32) -> Result<u32, PointsError> {
+        match self {
+            Self::Credit => balance
+                .checked_add(amount)
+                .ok_or(PointsError::Overflow { balance, amount }),
+            Self::Debit => balance
+                .checked_sub(amount)
+                .ok_or(PointsError::InsufficientPoints { balance, required: amount }),
+        }
+    }
+}
+
+impl PointsEntry {
+    pub fn new(kind: PointsEntryKind, source: PointsSource, amount: u32, timestamp: u64, balance: u32) -> Self {
+        Self {
+            kind,
+            source,
+            amount,
+            timestamp,
+            balance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_checks_both_ends_of_the_balance() {
+        assert_eq!(PointsEntryKind::Credit.apply(10, 5), Ok(15));
+        assert_eq!(PointsEntryKind::Credit.apply(u32::MAX - 5, 5), Ok(u32::MAX));
+        assert_eq!(
+            PointsEntryKind::Credit.apply(u32::MAX - 5, 6),
+            Err(PointsError::Overflow { balance: u32::MAX - 5, amount: 6 })
+        );
+
+        assert_eq!(PointsEntryKind::Debit.apply(10, 10), Ok(0));
+        assert_eq!(
+            PointsEntryKind::Debit.apply(10, 11),
+            Err(PointsError::InsufficientPoints { balance: 10, required: 11 })
+        );
+    }
+}
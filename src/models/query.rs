@@ -0,0 +1,104 @@
+use schemars::JsonSchema;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize}};
+use crate::models::task::{Task, TaskId, TaskState, Priority};
+use crate::models::admin::ContractConfig;
+
+// === Core State and Action Enums ===
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TaskSortKey {
+    Deadline,
+    Priority,
+    EstimatedTime,
+    Title,
+}
+
+// === Core Data Structures ===
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskQuery {
+    pub from_index: Option<u64>,
+    pub limit: Option<u64>,
+    pub state: Option<TaskState>,
+    pub priority: Option<Priority>,
+    pub deadline_from: Option<u64>,
+    pub deadline_to: Option<u64>,
+    pub parent_task_id: Option<TaskId>,
+    pub roots_only: Option<bool>,
+    pub sort_by: Option<TaskSortKey>,
+    pub descending: Option<bool>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    pub total: u64,
+}
+
+// === Core Implementations ===
+impl TaskQuery {
+    pub fn matches(&self, task: &Task) -> bool {
+        self.state.is_none_or(|state| task.state == state) &&
+        self.priority.is_none_or(|priority| task.priority == priority) &&
+        self.matches_deadline(task.deadline) &&
+        self.parent_task_id.as_ref().is_none_or(|parent_id| task.parent_task_id.as_ref() == Some(parent_id)) &&
+        (!self.roots_only.unwrap_or(false) || task.parent_task_id.is_none())
+    }
+
+    // Checked against the index key, before the task is loaded
+    pub fn matches_deadline(&self, deadline: u64) -> bool {
+        self.deadline_from.is_none_or(|from| deadline >= from) &&
+        self.deadline_to.is_none_or(|to| deadline <= to)
+    }
+
+    // A walk in deadline order can stop once it has left the deadline range
+    pub fn past_deadline_range(&self, deadline: u64) -> bool {
+        if self.descending.unwrap_or(false) {
+            self.deadline_from.is_some_and(|from| deadline < from)
+        } else {
+            self.deadline_to.is_some_and(|to| deadline > to)
+        }
+    }
+
+    // Every task in the requested states matches, so the index sizes give the total
+    pub fn filters_by_state_only(&self) -> bool {
+        self.priority.is_none() &&
+        self.deadline_from.is_none() &&
+        self.deadline_to.is_none() &&
+        self.parent_task_id.is_none() &&
+        !self.roots_only.unwrap_or(false)
+    }
+
+    pub fn sort(&self, tasks: &mut [Task]) {
+        match self.sort_by.unwrap_or(TaskSortKey::Deadline) {
+            TaskSortKey::Deadline => tasks.sort_by_key(|task| task.deadline),
+            TaskSortKey::Priority => tasks.sort_by_key(|task| task.priority),
+            TaskSortKey::EstimatedTime => tasks.sort_by_key(|task| task.estimated_time),
+            TaskSortKey::Title => tasks.sort_by(|a, b| a.title.cmp(&b.title)),
+        }
+        if self.descending.unwrap_or(false) {
+            tasks.reverse();
+        }
+    }
+
+    pub fn page_bounds(&self, config: &ContractConfig) -> (usize, usize) {
+        let from_index = self.from_index.unwrap_or(0) as usize;
+        let limit = self.limit.unwrap_or(config.default_page_limit).min(config.max_page_limit) as usize;
+        (from_index, limit)
+    }
+
+    /// Filters, sorts and slices the tasks into a single page.
+    pub fn apply(&self, tasks: Vec<Task>, config: &ContractConfig) -> TaskPage {
+        let mut matching: Vec<Task> = tasks.into_iter().filter(|task| self.matches(task)).collect();
+        self.sort(&mut matching);
+
+        let (from_index, limit) = self.page_bounds(config);
+        let total = matching.len() as u64;
+        let tasks = matching.into_iter().skip(from_index).take(limit).collect();
+
+        TaskPage { tasks, total }
+    }
+}
@@ -0,0 +1,605 @@
+use schemars::JsonSchema;
+use std::collections::HashSet;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize},
+    env};
+
+use crate::models::config::recurrence::*;
+use crate::models::time_slot::DayOfWeek;
+use crate::models::calendar::{day_index, weekday_index, days_from_civil, civil_from_days, days_in_month};
+
+// === Error Hierarchy ===
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RecurrenceError {
+    EmptyDays,
+    InvalidPattern,
+    ZeroInterval,
+    IntervalTooLarge { provided_interval: u32 },
+    MonthDay { provided_day: i8 },
+    WeekOfMonth { provided_week: i8 },
+    Month { provided_month: u8 },
+    EndBeforeStart,
+    Count { provided_count: u32 },
+    NeverOccurs,
+}
+
+impl std::fmt::Display for RecurrenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyDays => write!(f, "Recurrence needs at least one day"),
+            Self::InvalidPattern => write!(f, "Recurrence rule combines parts its frequency doesn't support"),
+            Self::ZeroInterval => write!(f, "Recurrence interval must be greater than zero"),
+            Self::IntervalTooLarge { provided_interval } => {
+                write!(f, "Recurrence interval must be at most {} (interval: {})", MAX_INTERVAL, provided_interval)
+            },
+            Self::MonthDay { provided_day } => {
+                write!(f, "Month day must be between 1 and 31 or -31 and -1 (day: {})", provided_day)
+            },
+            Self::WeekOfMonth { provided_week } => {
+                write!(f, "Week of month must be between 1 and 5 or -5 and -1 (week: {})", provided_week)
+            },
+            Self::Month { provided_month } => {
+                write!(f, "Month must be between 1 and 12 (month: {})", provided_month)
+            },
+            Self::EndBeforeStart => write!(f, "Recurrence ends before it starts"),
+            Self::Count { provided_count } => {
+                write!(f, "Occurrence count must be between 1 and {} (count: {})", MAX_OCCURRENCE_COUNT, provided_count)
+            },
+            Self::NeverOccurs => {
+                write!(f, "Recurrence has no occurrence within {} periods of its start", MAX_PERIODS_SCANNED)
+            }
+        }
+    }
+}
+
+// === Core Data Structures ===
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+    PartialEq, Debug, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Frequency {
+    Daily,
+    // Weekly on specific_days, interval ignored
+    Custom,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// Follows iCalendar RRULE semantics: FREQ, INTERVAL, BYDAY, BYMONTHDAY, BYMONTH,
+// BYDAY with an ordinal, DTSTART, UNTIL and COUNT. Days are local to the owner's UTC offset.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize,
+    Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RecurrencePattern {
+    pub frequency: Frequency,
+    pub interval: Option<u32>,
+    // Weekly, Monthly and Yearly: the weekdays it occurs on
+    pub specific_days: Option<Vec<DayOfWeek>>,
+    // Monthly and Yearly: day of the month, negative counts from the end
+    pub month_day: Option<i8>,
+    // Monthly and Yearly: nth specific_days weekday of the month, negative counts from the end
+    pub week_of_month: Option<i8>,
+    // Yearly: month of the year, defaults to the start month
+    pub month: Option<u8>,
+    // Intervals and default days are counted from here, set on creation when omitted
+    pub start: Option<u64>,
+    // Last moment an occurrence may fall on
+    pub until: Option<u64>,
+    // Total number of occurrences, counted from start
+    pub count: Option<u32>,
+}
+
+// Layout before monthly and yearly rules and end conditions
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct RecurrencePatternV0 {
+    frequency: Frequency,
+    interval: Option<u32>,
+    specific_days: Option<Vec<DayOfWeek>>,
+}
+
+impl From<RecurrencePatternV0> for RecurrencePattern {
+    fn from(old: RecurrencePatternV0) -> Self {
+        Self {
+            frequency: old.frequency,
+            interval: old.interval,
+            specific_days: old.specific_days,
+            month_day: None,
+            week_of_month: None,
+            month: None,
+            // Old daily intervals were counted from the Unix epoch
+            start: None,
+            until: None,
+            count: None,
+        }
+    }
+}
+
+// === Core Implementations ===
+impl RecurrencePattern {
+    pub fn new_daily() -> Self {
+        Self::with_frequency(Frequency::Daily, Some(1), None)
+    }
+
+    pub fn new_custom(days: Vec<DayOfWeek>) -> Self {
+        if days.is_empty() {
+            env::panic_str("Must specify at least one day");
+        }
+
+        let mut unique_days: Vec<DayOfWeek> = days.into_iter().collect::<HashSet<_>>().into_iter().collect();
+        unique_days.sort();
+
+        Self::with_frequency(Frequency::Custom, None, Some(unique_days))
+    }
+
+    fn with_frequency(frequency: Frequency, interval: Option<u32>, specific_days: Option<Vec<DayOfWeek>>) -> Self {
+        Self {
+            frequency,
+            interval,
+            specific_days,
+            month_day: None,
+            week_of_month: None,
+            month: None,
+            start: None,
+            until: None,
+            count: None,
+        }
+    }
+
+    pub fn anchored(mut self, current_time: u64) -> Self {
+        self.start.get_or_insert(current_time);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), RecurrenceError> {
+        match self.interval {
+            Some(0) => return Err(RecurrenceError::ZeroInterval),
+            Some(interval) if interval > MAX_INTERVAL => {
+                return Err(RecurrenceError::IntervalTooLarge { provided_interval: interval });
+            },
+            _ => ()
+        }
+
+        let has_days = self.specific_days.as_ref().is_some_and(|days| !days.is_empty());
+        if self.specific_days.is_some() && !has_days {
+            return Err(RecurrenceError::EmptyDays);
+        }
+
+        let monthly_rules = self.month_day.is_some() || self.week_of_month.is_some();
+        match self.frequency {
+            Frequency::Custom if !has_days => return Err(RecurrenceError::EmptyDays),
+            // Daily rules occur on every day of their interval, so weekdays would be ignored
+            Frequency::Daily if self.specific_days.is_some() => return Err(RecurrenceError::InvalidPattern),
+            Frequency::Daily | Frequency::Custom | Frequency::Weekly
+                if monthly_rules || self.month.is_some() => {
+                return Err(RecurrenceError::InvalidPattern);
+            },
+            Frequency::Monthly if self.month.is_some() => return Err(RecurrenceError::InvalidPattern),
+            _ => ()
+        }
+
+        if let Some(day) = self.month_day {
+            if day == 0 || !(-31..=31).contains(&day) {
+                return Err(RecurrenceError::MonthDay { provided_day: day });
+            }
+        }
+        if let Some(week) = self.week_of_month {
+            if week == 0 || !(-5..=5).contains(&week) {
+                return Err(RecurrenceError::WeekOfMonth { provided_week: week });
+            }
+            if !has_days {
+                return Err(RecurrenceError::EmptyDays);
+            }
+            if self.month_day.is_some() {
+                return Err(RecurrenceError::InvalidPattern);
+            }
+        }
+        if let Some(month) = self.month {
+            if !(1..=12).contains(&month) {
+                return Err(RecurrenceError::Month { provided_month: month });
+            }
+        }
+
+        if self.until.is_some() && self.count.is_some() {
+            return Err(RecurrenceError::InvalidPattern);
+        }
+        if let (Some(start), Some(until)) = (self.start, self.until) {
+            if until < start {
+                return Err(RecurrenceError::EndBeforeStart);
+            }
+        }
+        if let Some(count) = self.count {
+            if count == 0 || count > MAX_OCCURRENCE_COUNT {
+                return Err(RecurrenceError::Count { provided_count: count });
+            }
+        }
+
+        // Rules such as February 30th or an until before the first match pass the checks above
+        let start_day = self.start.map_or(0, |start| day_index(start, 0));
+        if self.first_occurrence_day(start_day, 0).is_none() {
+            return Err(RecurrenceError::NeverOccurs);
+        }
+        Ok(())
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    // Occurrence days in [from_day, to_day], ascending
+    pub fn occurrence_days(&self, from_day: u64, to_day: u64, utc_offset_minutes: i32) -> Vec<u64> {
+        let mut days = Vec::new();
+        self.walk(from_day, utc_offset_minutes, |day| {
+            if day > to_day {
+                return false;
+            }
+            days.push(day);
+            true
+        });
+        days
+    }
+
+    // None once the series has ended
+    pub fn first_occurrence_day(&self, from_day: u64, utc_offset_minutes: i32) -> Option<u64> {
+        let mut first = None;
+        self.walk(from_day, utc_offset_minutes, |day| {
+            first = Some(day);
+            false
+        });
+        first
+    }
+
+    pub fn next_occurrence_day(&self, after_day: u64, utc_offset_minutes: i32) -> Option<u64> {
+        self.first_occurrence_day(after_day + 1, utc_offset_minutes)
+    }
+
+    pub fn occurs_on_day(&self, day: u64, utc_offset_minutes: i32) -> bool {
+        self.occurrence_days(day, day, utc_offset_minutes).contains(&day)
+    }
+
+    // Visits occurrences from from_day onwards until visit returns false or the series ends
+    fn walk(&self, from_day: u64, utc_offset_minutes: i32, mut visit: impl FnMut(u64) -> bool) {
+        let anchor = self.start.map_or(0, |start| day_index(start, utc_offset_minutes)) as i64;
+        let until = self.until.map(|until| day_index(until, utc_offset_minutes) as i64);
+        let from_day = from_day as i64;
+
+        // Counted series have to be walked from their first occurrence
+        let first_period = if self.count.is_some() || from_day <= anchor {
+            0
+        } else {
+            self.period_of(from_day, anchor)
+        };
+        let mut seen = 0;
+
+        for period in first_period..first_period + MAX_PERIODS_SCANNED as i64 {
+            if until.is_some_and(|until| self.period_start(period, anchor) > until) {
+                return;
+            }
+            for day in self.period_days(period, anchor) {
+                if day < anchor {
+                    continue;
+                }
+                if until.is_some_and(|until| day > until) {
+                    return;
+                }
+                seen += 1;
+                if self.count.is_some_and(|count| seen > count) {
+                    return;
+                }
+                if day >= from_day && !visit(day as u64) {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn step(&self) -> i64 {
+        match self.frequency {
+            Frequency::Custom => 1,
+            _ => self.interval.unwrap_or(1).max(1) as i64,
+        }
+    }
+
+    fn weekdays(&self) -> Vec<i64> {
+        let mut weekdays: Vec<i64> = self.specific_days
+            .iter()
+            .flatten()
+            .map(|day| day.clone() as i64)
+            .collect();
+        weekdays.sort();
+        weekdays.dedup();
+        weekdays
+    }
+
+    fn period_of(&self, day: i64, anchor: i64) -> i64 {
+        let elapsed = match self.frequency {
+            Frequency::Daily => day - anchor,
+            Frequency::Custom | Frequency::Weekly => week_of(day) - week_of(anchor),
+            Frequency::Monthly => month_of(day) - month_of(anchor),
+            Frequency::Yearly => civil_from_days(day).0 - civil_from_days(anchor).0,
+        };
+        elapsed / self.step()
+    }
+
+    fn period_start(&self, period: i64, anchor: i64) -> i64 {
+        let offset = period * self.step();
+        match self.frequency {
+            Frequency::Daily => anchor + offset,
+            Frequency::Custom | Frequency::Weekly => (week_of(anchor) + offset) * 7 - 3,
+            Frequency::Monthly => {
+                let month = month_of(anchor) + offset;
+                days_from_civil(month.div_euclid(12), month.rem_euclid(12) as u32 + 1, 1)
+            },
+            Frequency::Yearly => days_from_civil(civil_from_days(anchor).0 + offset, 1, 1),
+        }
+    }
+
+    fn period_days(&self, period: i64, anchor: i64) -> Vec<i64> {
+        let period_start = self.period_start(period, anchor);
+        match self.frequency {
+            Frequency::Daily => vec![period_start],
+            Frequency::Custom | Frequency::Weekly => {
+                let weekdays = self.weekdays();
+                if weekdays.is_empty() {
+                    vec![period_start + weekday_index(anchor)]
+                } else {
+                    weekdays.into_iter().map(|weekday| period_start + weekday).collect()
+                }
+            },
+            Frequency::Monthly | Frequency::Yearly => {
+                let (_, anchor_month, anchor_day) = civil_from_days(anchor);
+                let (year, month, _) = civil_from_days(period_start);
+                let month = match self.frequency {
+                    Frequency::Yearly => self.month.map_or(anchor_month, u32::from),
+                    _ => month,
+                };
+                self.month_days(year, month, anchor_day)
+            }
+        }
+    }
+
+    fn month_days(&self, year: i64, month: u32, anchor_day: u32) -> Vec<i64> {
+        let first = days_from_civil(year, month, 1);
+        let length = days_in_month(year, month) as i64;
+        let last = first + length - 1;
+        let weekdays = self.weekdays();
+
+        let mut days: Vec<i64> = if let Some(week) = self.week_of_month {
+            let week = week as i64;
+            weekdays.iter()
+                .map(|&weekday| if week > 0 {
+                    first + (weekday - weekday_index(first)).rem_euclid(7) + (week - 1) * 7
+                } else {
+                    last - (weekday_index(last) - weekday).rem_euclid(7) + (week + 1) * 7
+                })
+                .filter(|day| (first..=last).contains(day))
+                .collect()
+        } else if let Some(month_day) = self.month_day {
+            let day = if month_day > 0 { first + month_day as i64 - 1 } else { last + month_day as i64 + 1 };
+            // RRULE skips months too short for the day; specific_days narrows it further
+            Some(day)
+                .filter(|day| (first..=last).contains(day))
+                .filter(|day| weekdays.is_empty() || weekdays.contains(&weekday_index(*day)))
+                .into_iter()
+                .collect()
+        } else if !weekdays.is_empty() {
+            (first..=last).filter(|day| weekdays.contains(&weekday_index(*day))).collect()
+        } else {
+            Some(first + anchor_day as i64 - 1)
+                .filter(|day| *day <= last)
+                .into_iter()
+                .collect()
+        };
+        days.sort();
+        days
+    }
+}
+
+// Monday-based weeks; day 0 was a Thursday
+fn week_of(day: i64) -> i64 {
+    (day + 3).div_euclid(7)
+}
+
+fn month_of(day: i64) -> i64 {
+    let (year, month, _) = civil_from_days(day);
+    year * 12 + month as i64 - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::calendar::day_start;
+
+    fn day(year: i64, month: u32, day: u32) -> u64 {
+        days_from_civil(year, month, day) as u64
+    }
+
+    fn starting(frequency: Frequency, start_day: u64) -> RecurrencePattern {
+        RecurrencePattern {
+            frequency,
+            start: Some(day_start(start_day, 0)),
+            ..RecurrencePattern::new_daily()
+        }
+    }
+
+    fn dates(pattern: &RecurrencePattern, from_day: u64, to_day: u64) -> Vec<(i64, u32, u32)> {
+        assert!(pattern.is_valid());
+        pattern.occurrence_days(from_day, to_day, 0)
+            .into_iter()
+            .map(|day| civil_from_days(day as i64))
+            .collect()
+    }
+
+    #[test]
+    fn daily_intervals_count_from_the_start() {
+        let pattern = RecurrencePattern { interval: Some(3), ..starting(Frequency::Daily, day(2024, 1, 1)) };
+        assert_eq!(
+            dates(&pattern, day(2024, 1, 1), day(2024, 1, 10)),
+            [(2024, 1, 1), (2024, 1, 4), (2024, 1, 7), (2024, 1, 10)]
+        );
+        assert_eq!(dates(&pattern, day(2024, 1, 5), day(2024, 1, 10)), [(2024, 1, 7), (2024, 1, 10)]);
+        assert_eq!(dates(&pattern, day(2023, 12, 1), day(2024, 1, 3)), [(2024, 1, 1)]);
+    }
+
+    #[test]
+    fn weekly_rules_follow_byday_and_the_interval() {
+        // 2024-01-01 was a Monday
+        let every_other_week = RecurrencePattern {
+            frequency: Frequency::Weekly,
+            interval: Some(2),
+            ..RecurrencePattern::new_custom(vec![DayOfWeek::Thursday, DayOfWeek::Tuesday])
+        }.anchored(day_start(day(2024, 1, 1), 0));
+        assert_eq!(
+            dates(&every_other_week, day(2024, 1, 1), day(2024, 1, 31)),
+            [(2024, 1, 2), (2024, 1, 4), (2024, 1, 16), (2024, 1, 18), (2024, 1, 30)]
+        );
+
+        // Custom is weekly whatever the interval
+        let custom = RecurrencePattern {
+            interval: Some(2),
+            ..RecurrencePattern::new_custom(vec![DayOfWeek::Tuesday])
+        }.anchored(day_start(day(2024, 1, 1), 0));
+        assert_eq!(
+            dates(&custom, day(2024, 1, 1), day(2024, 1, 23)),
+            [(2024, 1, 2), (2024, 1, 9), (2024, 1, 16), (2024, 1, 23)]
+        );
+
+        // Without BYDAY a weekly rule repeats the start's weekday
+        let weekly = starting(Frequency::Weekly, day(2024, 1, 3));
+        assert_eq!(
+            dates(&weekly, day(2024, 1, 1), day(2024, 1, 20)),
+            [(2024, 1, 3), (2024, 1, 10), (2024, 1, 17)]
+        );
+    }
+
+    #[test]
+    fn monthly_rules_follow_bymonthday_and_ordinal_weekdays() {
+        let first = RecurrencePattern { month_day: Some(1), ..starting(Frequency::Monthly, day(2024, 1, 1)) };
+        assert_eq!(
+            dates(&first, day(2024, 1, 1), day(2024, 4, 30)),
+            [(2024, 1, 1), (2024, 2, 1), (2024, 3, 1), (2024, 4, 1)]
+        );
+
+        let second_tuesday = RecurrencePattern {
+            frequency: Frequency::Monthly,
+            week_of_month: Some(2),
+            ..RecurrencePattern::new_custom(vec![DayOfWeek::Tuesday])
+        }.anchored(day_start(day(2024, 1, 1), 0));
+        assert_eq!(
+            dates(&second_tuesday, day(2024, 1, 1), day(2024, 3, 31)),
+            [(2024, 1, 9), (2024, 2, 13), (2024, 3, 12)]
+        );
+
+        let last_friday = RecurrencePattern {
+            frequency: Frequency::Monthly,
+            week_of_month: Some(-1),
+            ..RecurrencePattern::new_custom(vec![DayOfWeek::Friday])
+        }.anchored(day_start(day(2024, 1, 1), 0));
+        assert_eq!(
+            dates(&last_friday, day(2024, 1, 1), day(2024, 3, 31)),
+            [(2024, 1, 26), (2024, 2, 23), (2024, 3, 29)]
+        );
+
+        // BYDAY narrows BYMONTHDAY to the months where the day falls on it
+        let friday_the_13th = RecurrencePattern {
+            frequency: Frequency::Monthly,
+            month_day: Some(13),
+            ..RecurrencePattern::new_custom(vec![DayOfWeek::Friday])
+        }.anchored(day_start(day(2024, 1, 1), 0));
+        assert_eq!(
+            dates(&friday_the_13th, day(2024, 1, 1), day(2024, 12, 31)),
+            [(2024, 9, 13), (2024, 12, 13)]
+        );
+    }
+
+    #[test]
+    fn month_end_days_skip_short_months_or_count_back_from_the_end() {
+        let thirty_first = RecurrencePattern { month_day: Some(31), ..starting(Frequency::Monthly, day(2024, 1, 1)) };
+        assert_eq!(
+            dates(&thirty_first, day(2024, 1, 1), day(2024, 6, 30)),
+            [(2024, 1, 31), (2024, 3, 31), (2024, 5, 31)]
+        );
+
+        // A start on the 31st repeats the same way
+        let anchored = starting(Frequency::Monthly, day(2024, 1, 31));
+        assert_eq!(dates(&anchored, day(2024, 1, 1), day(2024, 6, 30)), dates(&thirty_first, day(2024, 1, 1), day(2024, 6, 30)));
+
+        let last_day = RecurrencePattern { month_day: Some(-1), ..starting(Frequency::Monthly, day(2024, 1, 1)) };
+        assert_eq!(
+            dates(&last_day, day(2024, 1, 1), day(2024, 4, 30)),
+            [(2024, 1, 31), (2024, 2, 29), (2024, 3, 31), (2024, 4, 30)]
+        );
+        assert_eq!(dates(&last_day, day(2025, 2, 1), day(2025, 2, 28)), [(2025, 2, 28)]);
+
+        // Feb 29 only comes round in leap years
+        let leap_day = starting(Frequency::Yearly, day(2024, 2, 29));
+        assert_eq!(leap_day.next_occurrence_day(day(2024, 2, 29), 0), Some(day(2028, 2, 29)));
+    }
+
+    #[test]
+    fn monthly_and_yearly_intervals_skip_whole_periods() {
+        let every_other_month = RecurrencePattern {
+            interval: Some(2),
+            month_day: Some(15),
+            ..starting(Frequency::Monthly, day(2024, 1, 1))
+        };
+        assert_eq!(
+            dates(&every_other_month, day(2024, 1, 1), day(2024, 6, 30)),
+            [(2024, 1, 15), (2024, 3, 15), (2024, 5, 15)]
+        );
+        assert_eq!(dates(&every_other_month, day(2024, 4, 1), day(2024, 8, 31)), [(2024, 5, 15), (2024, 7, 15)]);
+
+        let every_third_year = RecurrencePattern {
+            interval: Some(3),
+            month: Some(7),
+            month_day: Some(4),
+            ..starting(Frequency::Yearly, day(2024, 1, 1))
+        };
+        assert_eq!(
+            dates(&every_third_year, day(2024, 1, 1), day(2031, 12, 31)),
+            [(2024, 7, 4), (2027, 7, 4), (2030, 7, 4)]
+        );
+    }
+
+    #[test]
+    fn until_and_count_end_the_series() {
+        let counted = RecurrencePattern { count: Some(3), ..starting(Frequency::Daily, day(2024, 1, 1)) };
+        assert_eq!(dates(&counted, day(2024, 1, 1), day(2024, 1, 31)), [(2024, 1, 1), (2024, 1, 2), (2024, 1, 3)]);
+        assert_eq!(dates(&counted, day(2024, 1, 2), day(2024, 1, 31)), [(2024, 1, 2), (2024, 1, 3)]);
+        assert_eq!(counted.next_occurrence_day(day(2024, 1, 3), 0), None);
+
+        let until = RecurrencePattern {
+            until: Some(day_start(day(2024, 1, 3), 0)),
+            ..starting(Frequency::Daily, day(2024, 1, 1))
+        };
+        assert_eq!(dates(&until, day(2024, 1, 1), day(2024, 1, 31)), [(2024, 1, 1), (2024, 1, 2), (2024, 1, 3)]);
+        assert_eq!(until.first_occurrence_day(day(2024, 1, 4), 0), None);
+    }
+
+    #[test]
+    fn rules_without_occurrences_are_rejected() {
+        let february_30 = RecurrencePattern {
+            month: Some(2),
+            month_day: Some(30),
+            ..starting(Frequency::Yearly, day(2024, 1, 1))
+        };
+        assert_eq!(february_30.validate(), Err(RecurrenceError::NeverOccurs));
+        let february_29 = RecurrencePattern { month_day: Some(29), ..february_30.clone() };
+        assert_eq!(dates(&february_29, day(2024, 1, 1), day(2028, 12, 31)), [(2024, 2, 29), (2028, 2, 29)]);
+
+        // February has no 31st and March's falls after the end
+        let ended = RecurrencePattern {
+            month_day: Some(31),
+            until: Some(day_start(day(2024, 3, 30), 0)),
+            ..starting(Frequency::Monthly, day(2024, 2, 1))
+        };
+        assert_eq!(ended.validate(), Err(RecurrenceError::NeverOccurs));
+
+        let daily_on_days = RecurrencePattern {
+            specific_days: Some(vec![DayOfWeek::Monday]),
+            ..starting(Frequency::Daily, day(2024, 1, 1))
+        };
+        assert_eq!(daily_on_days.validate(), Err(RecurrenceError::InvalidPattern));
+    }
+}
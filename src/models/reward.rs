@@ -2,12 +2,13 @@ use schemars::JsonSchema;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
-    env, AccountId};
+    AccountId};
 use crate::models::traits::{
-    Storable, StorageError, StorageMetrics,
+    Storable, StorageError,
     Ownable, OwnershipError};
 
-use crate::models::config::{reward::*, storage::*};
+use crate::models::config::prefix;
+use crate::models::admin::ContractConfig;
 
 pub type RewardId = String;
 
@@ -98,8 +99,8 @@ pub struct Reward {
 
 // === Trait Definitions ===
 pub trait RewardValidation {
-    fn validate_title(&mut self) -> Result<(), RewardValidationError>;
-    fn validate_description(&mut self) -> Result<(), RewardValidationError>; 
+    fn validate_title(&mut self, config: &ContractConfig) -> Result<(), RewardValidationError>;
+    fn validate_description(&mut self, config: &ContractConfig) -> Result<(), RewardValidationError>;
     fn validate_cost(&mut self) -> Result<(), RewardValidationError>;
     fn validate_state_for_action(&self, action: RewardAction) -> Result<(), RewardStateError>;
 }
@@ -153,13 +154,15 @@ impl std::fmt::Display for RewardValidationError {
 // === Core Implementations ===
 impl Reward {
     pub fn new(
+        id: RewardId,
         title: String,
         description: String,
         cost: u32,
         owner_id: AccountId,
+        config: &ContractConfig,
     ) -> Result<Self, RewardError> {
         let mut reward = Self {
-            id: format!("reward-{}-{}", owner_id, env::block_timestamp()),
+            id,
             title,
             description,
             cost,
@@ -167,19 +170,19 @@ impl Reward {
             state: RewardState::Active,
         };
 
-        reward.validate()?;
+        reward.validate(config)?;
         Ok(reward)
     }
 
-    pub fn validate(&mut self) -> Result<(), RewardError> {
-        self.validate_title()
+    pub fn validate(&mut self, config: &ContractConfig) -> Result<(), RewardError> {
+        self.validate_title(config)
             .map_err(RewardError::Validation)?;
-        self.validate_description()
+        self.validate_description(config)
             .map_err(RewardError::Validation)?;
         self.validate_cost()
             .map_err(RewardError::Validation)?;
-        self.validate_storage()
-            .map_err(|e| RewardError::Storage(e))?;
+        self.validate_storage(config)
+            .map_err(RewardError::Storage)?;
         Ok(())
     }
 
@@ -190,17 +193,14 @@ impl Reward {
                 Ok(())
             },
             _ => Err(RewardError::State(RewardStateError::InvalidTransition {
-                from: self.state.clone(),
+                from: self.state,
                 to: new_state,
             })),
         }
     }
 
     pub fn is_affordable(&self, available_points: u32) -> bool {
-        match available_points.checked_sub(self.cost) {
-            Some(_) => true,
-            None => false
-        }
+        available_points.checked_sub(self.cost).is_some()
     }
     
 }
@@ -212,39 +212,27 @@ impl Ownable for Reward {
 }
 
 impl Storable for Reward {
-    const BASE_STORAGE: u64 = REWARD_BASE_STORAGE;
-    const MAX_STORAGE: u64 = REWARD_MAX_STORAGE;
+    const MAP_PREFIX: &'static str = prefix::REWARDS;
+    const OWNER_INDEX_PREFIX: &'static str = prefix::REWARDS_PER_OWNER;
 
-    fn calculate_storage_metrics(&self) -> StorageMetrics {
-        
-        let dynamic_size = 
-            self.id.len() as u64 +
-            self.title.len() as u64 +
-            self.description.len() as u64 +
-            self.owner_id.to_string().len() as u64;
-            
-        let total_bytes = Self::BASE_STORAGE + dynamic_size;
-        let cost_per_byte = env::storage_byte_cost().as_yoctonear();
-        let metrics = StorageMetrics {
-            base_size: Self::BASE_STORAGE,
-            dynamic_size,
-            total_bytes,
-            cost_per_byte,
-            total_cost: cost_per_byte * total_bytes as u128,
-        };
-        metrics
+    fn storage_key(&self) -> &str {
+        &self.id
+    }
+
+    fn max_storage(config: &ContractConfig) -> u64 {
+        config.reward_max_storage
     }
 }
 
 impl RewardValidation for Reward {
-    fn validate_title(&mut self) -> Result<(), RewardValidationError> {
+    fn validate_title(&mut self, config: &ContractConfig) -> Result<(), RewardValidationError> {
         if self.title.is_empty() {
             return Err(RewardValidationError::Title {
                 reason: RewardTitleError::Empty,
                 current_length: 0,
             });
         }
-        if self.title.len() > MAX_TITLE_LENGTH {
+        if self.title.len() > config.reward_max_title_length as usize {
             return Err(RewardValidationError::Title {
                 reason: RewardTitleError::TooLong,
                 current_length: self.title.len(),
@@ -254,8 +242,8 @@ impl RewardValidation for Reward {
             let code = c as u32;
             // Allow tab (0x09), line feed (0x0A), and carriage return (0x0D)
             // Prohibit other control characters
-            (code <= 0x08) || (code >= 0x0B && code <= 0x0C) || 
-            (code >= 0x0E && code <= 0x1F) || (code == 0x7F)
+            (code <= 0x08) || (0x0B..=0x0C).contains(&code) || 
+            (0x0E..=0x1F).contains(&code) || (code == 0x7F)
         }) {
             return Err(RewardValidationError::Title {
                 reason: RewardTitleError::InvalidCharacters,
@@ -266,8 +254,8 @@ impl RewardValidation for Reward {
         Ok(())
     }
 
-    fn validate_description(&mut self) -> Result<(), RewardValidationError> {
-        if self.description.len() > MAX_DESCRIPTION_LENGTH {
+    fn validate_description(&mut self, config: &ContractConfig) -> Result<(), RewardValidationError> {
+        if self.description.len() > config.reward_max_description_length as usize {
             return Err(RewardValidationError::Description {
                 reason: RewardDescriptionError::TooLong,
                 current_length: self.description.len(),
@@ -278,8 +266,8 @@ impl RewardValidation for Reward {
             let code = c as u32;
             // Allow tab (0x09), line feed (0x0A), and carriage return (0x0D)
             // Prohibit other control characters
-            (code <= 0x08) || (code >= 0x0B && code <= 0x0C) || 
-            (code >= 0x0E && code <= 0x1F) || (code == 0x7F)
+            (code <= 0x08) || (0x0B..=0x0C).contains(&code) || 
+            (0x0E..=0x1F).contains(&code) || (code == 0x7F)
         }) {
             return Err(RewardValidationError::Description {
                 reason: RewardDescriptionError::InvalidCharacters,
@@ -306,7 +294,7 @@ impl RewardValidation for Reward {
         match (&self.state, &action) {
             (RewardState::Completed, _) => {
                 Err(RewardStateError::InvalidActionForState {
-                    state: self.state.clone(),
+                    state: self.state,
                     action,
                 })
             }
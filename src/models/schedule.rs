@@ -0,0 +1,291 @@
+use schemars::JsonSchema;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize}};
+use crate::models::task::{Task, TaskId, TaskState, TaskTimeSlot};
+use crate::models::time_slot::TimeSlotId;
+use crate::models::config::time::*;
+use crate::models::admin::ContractConfig;
+
+// Half-open [start, end) nanosecond interval
+pub type Window = (u64, u64);
+
+// === Core Data Structures ===
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ScheduledTask {
+    pub task_id: TaskId,
+    pub time_slots: Vec<TaskTimeSlot>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SchedulePlan {
+    pub scheduled: Vec<ScheduledTask>,
+    pub unscheduled: Vec<TaskId>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
+    Debug, PartialEq, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ConflictSource {
+    Task(TaskId),
+    Break(TimeSlotId),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
+    Debug, PartialEq, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ScheduleConflict {
+    pub source: ConflictSource,
+    pub start_time: u64,
+    pub end_time: u64,
+}
+
+impl std::fmt::Display for ScheduleConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.source {
+            ConflictSource::Task(id) => write!(f, "task {}", id)?,
+            ConflictSource::Break(id) => write!(f, "break {}", id)?,
+        }
+        write!(f, " ({} - {})", self.start_time, self.end_time)
+    }
+}
+
+// === Window Arithmetic ===
+pub fn windows_overlap(a: Window, b: Window) -> bool {
+    a.0 < b.1 && a.1 > b.0
+}
+
+// Both lists ordered by start, without overlaps inside either of them
+pub fn sorted_windows_overlap(a: &[Window], b: &[Window]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if windows_overlap(a[i], b[j]) {
+            return true;
+        }
+        // Whichever ends first lies before everything left in the other list
+        if a[i].1 <= b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    false
+}
+
+pub fn merge_windows(mut windows: Vec<Window>) -> Vec<Window> {
+    windows.retain(|(start, end)| end > start);
+    windows.sort();
+
+    let mut merged: Vec<Window> = Vec::with_capacity(windows.len());
+    for (start, end) in windows {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+pub fn subtract_windows(free: Vec<Window>, busy: &[Window]) -> Vec<Window> {
+    let mut result = merge_windows(free);
+    for &(busy_start, busy_end) in busy {
+        result = result
+            .into_iter()
+            .flat_map(|(start, end)| {
+                if busy_end <= start || busy_start >= end {
+                    return vec![(start, end)];
+                }
+                let mut pieces = Vec::new();
+                if busy_start > start {
+                    pieces.push((start, busy_start));
+                }
+                if busy_end < end {
+                    pieces.push((busy_end, end));
+                }
+                pieces
+            })
+            .collect();
+    }
+    result
+}
+
+// === Planning ===
+/// Greedily fits unscheduled tasks into free windows, highest priority first
+/// and earliest deadline first within a priority. A task is only placed if its
+/// whole estimated time fits before its deadline.
+pub fn plan_tasks(tasks: &[Task], free_windows: Vec<Window>, now: u64, config: &ContractConfig) -> SchedulePlan {
+    let mut free = merge_windows(free_windows);
+    let min_chunk = config.min_chunk_minutes as u64 * NANOS_PER_MINUTE;
+
+    let mut candidates: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| matches!(task.state, TaskState::Created | TaskState::InProgress))
+        .filter(|task| task.time_slots.is_empty() && task.deadline > now)
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.priority.cmp(&a.priority)
+            .then(a.deadline.cmp(&b.deadline))
+            .then(a.id.cmp(&b.id))
+    });
+
+    let mut plan = SchedulePlan {
+        scheduled: Vec::new(),
+        unscheduled: Vec::new(),
+    };
+
+    for task in candidates {
+        // Slots must end strictly before the deadline
+        let limit = task.deadline - 1;
+        let mut remaining = task.estimated_time as u64 * NANOS_PER_MINUTE;
+        let mut chunks: Vec<Window> = Vec::new();
+
+        for &(start, end) in &free {
+            if remaining == 0 || start >= limit {
+                break;
+            }
+            let available = end.min(limit) - start;
+            if available < min_chunk && available < remaining {
+                continue;
+            }
+            let taken = available.min(remaining);
+            chunks.push((start, start + taken));
+            remaining -= taken;
+        }
+
+        if remaining > 0 {
+            plan.unscheduled.push(task.id.clone());
+            continue;
+        }
+
+        free = subtract_windows(free, &chunks);
+        plan.scheduled.push(ScheduledTask {
+            task_id: task.id.clone(),
+            time_slots: chunks
+                .into_iter()
+                .map(|(start_time, end_time)| TaskTimeSlot { start_time, end_time })
+                .collect(),
+        });
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+    use crate::models::task::Priority;
+
+    const NOW: u64 = 1_700_000_000_000_000_000;
+    const HOUR: u64 = 60 * NANOS_PER_MINUTE;
+
+    fn at(hours: u64, minutes: u64) -> u64 {
+        NOW + hours * HOUR + minutes * NANOS_PER_MINUTE
+    }
+
+    fn task(id: &str, priority: Priority, deadline: u64, estimated_time: u32, time_slots: Vec<TaskTimeSlot>) -> Task {
+        testing_env!(VMContextBuilder::new().block_timestamp(NOW).build());
+        Task::new(
+            id.to_string(),
+            id.to_string(),
+            String::new(),
+            priority,
+            deadline,
+            estimated_time,
+            time_slots,
+            "alice.near".parse().unwrap(),
+            &ContractConfig::default(),
+        ).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn schedule(tasks: &[Task], free_windows: Vec<Window>) -> SchedulePlan {
+        plan_tasks(tasks, free_windows, NOW, &ContractConfig::default())
+    }
+
+    fn placed(plan: &SchedulePlan, task_id: &str) -> Option<Vec<Window>> {
+        plan.scheduled.iter()
+            .find(|scheduled| scheduled.task_id == task_id)
+            .map(|scheduled| scheduled.time_slots.iter().map(|slot| (slot.start_time, slot.end_time)).collect())
+    }
+
+    #[test]
+    fn higher_priority_and_earlier_deadlines_go_first() {
+        let tasks = [
+            task("low", Priority::Low, at(5, 0), 60, Vec::new()),
+            task("high-late", Priority::High, at(20, 0), 60, Vec::new()),
+            task("high-early", Priority::High, at(10, 0), 60, Vec::new()),
+        ];
+        let plan = schedule(&tasks, vec![(at(1, 0), at(4, 0))]);
+
+        let order: Vec<&str> = plan.scheduled.iter().map(|scheduled| scheduled.task_id.as_str()).collect();
+        assert_eq!(order, ["high-early", "high-late", "low"]);
+        assert_eq!(placed(&plan, "high-early"), Some(vec![(at(1, 0), at(2, 0))]));
+        assert_eq!(placed(&plan, "high-late"), Some(vec![(at(2, 0), at(3, 0))]));
+        assert_eq!(placed(&plan, "low"), Some(vec![(at(3, 0), at(4, 0))]));
+    }
+
+    #[test]
+    fn work_is_split_into_chunks_of_at_least_min_chunk() {
+        let tasks = [
+            task("long", Priority::High, at(10, 0), 60, Vec::new()),
+            task("short", Priority::Low, at(10, 0), 10, Vec::new()),
+        ];
+        let plan = schedule(&tasks, vec![
+            (at(1, 0), at(1, 10)),
+            (at(2, 0), at(2, 30)),
+            (at(3, 0), at(4, 0)),
+        ]);
+
+        // Ten minutes is below min_chunk for the long task but enough for the short one
+        assert_eq!(placed(&plan, "long"), Some(vec![(at(2, 0), at(2, 30)), (at(3, 0), at(3, 30))]));
+        assert_eq!(placed(&plan, "short"), Some(vec![(at(1, 0), at(1, 10))]));
+        assert!(plan.unscheduled.is_empty());
+    }
+
+    #[test]
+    fn placement_stops_before_the_deadline() {
+        let tasks = [task("report", Priority::Medium, at(3, 30), 90, Vec::new())];
+        let plan = schedule(&tasks, vec![(at(1, 0), at(2, 0)), (at(2, 30), at(6, 0))]);
+        assert_eq!(placed(&plan, "report"), Some(vec![(at(1, 0), at(2, 0)), (at(2, 30), at(3, 0))]));
+
+        // Slots end strictly before the deadline, so work due right as it would finish doesn't fit
+        let tasks = [task("report", Priority::Medium, at(3, 0), 90, Vec::new())];
+        let plan = schedule(&tasks, vec![(at(1, 0), at(2, 0)), (at(2, 30), at(6, 0))]);
+        assert_eq!(plan.unscheduled, ["report"]);
+
+        // Only the time before the deadline counts, however much is free after it
+        let tasks = [task("report", Priority::Medium, at(2, 0), 90, Vec::new())];
+        let plan = schedule(&tasks, vec![(at(1, 0), at(6, 0))]);
+        assert!(plan.scheduled.is_empty());
+        assert_eq!(plan.unscheduled, ["report"]);
+    }
+
+    #[test]
+    fn tasks_that_do_not_fit_leave_the_time_to_others() {
+        let tasks = [
+            task("too-long", Priority::Critical, at(10, 0), 90, Vec::new()),
+            task("fits", Priority::Low, at(10, 0), 60, Vec::new()),
+        ];
+        let plan = schedule(&tasks, vec![(at(1, 0), at(2, 0))]);
+
+        assert_eq!(plan.unscheduled, ["too-long"]);
+        assert_eq!(placed(&plan, "fits"), Some(vec![(at(1, 0), at(2, 0))]));
+    }
+
+    #[test]
+    fn tasks_with_slots_are_left_alone() {
+        let slot = TaskTimeSlot { start_time: at(1, 0), end_time: at(2, 0) };
+        let tasks = [
+            task("booked", Priority::Critical, at(10, 0), 60, vec![slot]),
+            task("open", Priority::Low, at(10, 0), 60, Vec::new()),
+        ];
+        let plan = schedule(&tasks, vec![(at(1, 0), at(2, 0))]);
+
+        assert_eq!(placed(&plan, "booked"), None);
+        assert!(!plan.unscheduled.iter().any(|task_id| task_id == "booked"));
+        assert_eq!(placed(&plan, "open"), Some(vec![(at(1, 0), at(2, 0))]));
+    }
+}
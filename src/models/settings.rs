@@ -0,0 +1,126 @@
+use schemars::JsonSchema;
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize}};
+
+use crate::models::config::time::*;
+use crate::models::task::Priority;
+
+// Priority scaling applied to the overdue penalty, in percent
+const PRIORITY_PENALTY_SCALE: [u32; 4] = [50, 100, 150, 200];
+
+// === Error Hierarchy ===
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SettingsValidationError {
+    UtcOffset { provided_offset: i32 },
+    PenaltyPercent { provided_percent: u8 },
+}
+
+impl std::fmt::Display for SettingsValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UtcOffset { provided_offset } => {
+                write!(f, "UTC offset must be between {} and {} minutes (offset: {})",
+                    MIN_UTC_OFFSET_MINUTES, MAX_UTC_OFFSET_MINUTES, provided_offset)
+            },
+            Self::PenaltyPercent { provided_percent } => {
+                write!(f, "Penalty percentages must be at most 100 (percent: {})", provided_percent)
+            }
+        }
+    }
+}
+
+// === Core Data Structures ===
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountSettings {
+    // Applied to TimeSlot minutes and habit recurrence days
+    pub utc_offset_minutes: i32,
+}
+
+// Opt-in consequences for missed deadlines, as percentages of a task's reward_points
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PenaltyPolicy {
+    // Lost when the task becomes overdue, scaled by its priority
+    pub overdue_penalty_percent: u8,
+    // Earned when an overdue task is completed after all
+    pub late_completion_percent: u8,
+}
+
+// === Core Implementations ===
+impl AccountSettings {
+    pub fn validate(&self) -> Result<(), SettingsValidationError> {
+        if !(MIN_UTC_OFFSET_MINUTES..=MAX_UTC_OFFSET_MINUTES).contains(&self.utc_offset_minutes) {
+            return Err(SettingsValidationError::UtcOffset {
+                provided_offset: self.utc_offset_minutes,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl PenaltyPolicy {
+    pub fn validate(&self) -> Result<(), SettingsValidationError> {
+        for percent in [self.overdue_penalty_percent, self.late_completion_percent] {
+            if percent > 100 {
+                return Err(SettingsValidationError::PenaltyPercent {
+                    provided_percent: percent,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Never more than the task was worth
+    pub fn overdue_penalty(&self, reward_points: u32, priority: Priority) -> u32 {
+        let scale = PRIORITY_PENALTY_SCALE[priority as usize];
+        let penalty = reward_points as u64 * self.overdue_penalty_percent as u64 * scale as u64 / 10_000;
+        penalty.min(reward_points as u64) as u32
+    }
+
+    pub fn late_completion_points(&self, reward_points: u32) -> u32 {
+        (reward_points as u64 * self.late_completion_percent as u64 / 100) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(overdue_penalty_percent: u8, late_completion_percent: u8) -> PenaltyPolicy {
+        PenaltyPolicy { overdue_penalty_percent, late_completion_percent }
+    }
+
+    #[test]
+    fn overdue_penalty_scales_with_priority_up_to_the_reward() {
+        let half = policy(50, 0);
+        let priorities = [Priority::Low, Priority::Medium, Priority::High, Priority::Critical];
+        let penalties: Vec<u32> = priorities.iter().map(|priority| half.overdue_penalty(100, *priority)).collect();
+        assert_eq!(penalties, [25, 50, 75, 100]);
+
+        let full = policy(100, 0);
+        let penalties: Vec<u32> = priorities.iter().map(|priority| full.overdue_penalty(100, *priority)).collect();
+        assert_eq!(penalties, [50, 100, 100, 100]);
+
+        // Rounded down, and nothing without a percentage
+        assert_eq!(half.overdue_penalty(6, Priority::High), 4);
+        assert_eq!(policy(0, 0).overdue_penalty(100, Priority::Critical), 0);
+    }
+
+    #[test]
+    fn late_completion_points_are_a_share_of_the_reward() {
+        assert_eq!(policy(0, 50).late_completion_points(7), 3);
+        assert_eq!(policy(0, 100).late_completion_points(7), 7);
+        assert_eq!(policy(0, 0).late_completion_points(7), 0);
+        assert_eq!(policy(0, 100).late_completion_points(u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn validate_caps_percentages_at_100() {
+        assert_eq!(policy(100, 100).validate(), Ok(()));
+        assert_eq!(policy(101, 0).validate(), Err(SettingsValidationError::PenaltyPercent { provided_percent: 101 }));
+        assert_eq!(policy(0, 101).validate(), Err(SettingsValidationError::PenaltyPercent { provided_percent: 101 }));
+    }
+}
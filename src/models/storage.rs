@@ -0,0 +1,153 @@
+use schemars::JsonSchema;
+use near_sdk::{
+    env, AccountId,
+    json_types::U128,
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    serde::{Deserialize, Serialize}};
+
+use crate::models::task::TaskState;
+use crate::models::config::{prefix, storage::*};
+
+// === Core Data Structures ===
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq)]
+pub struct StorageAccount {
+    pub deposit: u128,
+    // Measured from env::storage_usage() deltas, including the account record itself
+    pub bytes_used: u64,
+}
+
+// NEP-145 view of an account's storage deposit
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    #[schemars(with = "String")]
+    pub total: U128,
+    #[schemars(with = "String")]
+    pub available: U128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    #[schemars(with = "String")]
+    pub min: U128,
+    #[schemars(with = "Option<String>")]
+    pub max: Option<U128>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EntityStorage {
+    pub count: u64,
+    pub bytes: u64,
+    #[schemars(with = "String")]
+    pub cost: U128,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageReport {
+    pub tasks: EntityStorage,
+    pub habits: EntityStorage,
+    pub rewards: EntityStorage,
+    pub time_slots: EntityStorage,
+    pub points_ledger: EntityStorage,
+    pub habit_history: EntityStorage,
+    // Per-account records: settings, points, nonce, deposit and index headers
+    pub account: EntityStorage,
+    pub total_bytes: u64,
+    #[schemars(with = "String")]
+    pub total_cost: U128,
+    // Bytes booked against the deposit from storage usage deltas
+    pub measured_bytes: u64,
+    pub balance: Option<StorageBalance>,
+}
+
+// === Storage Layout ===
+// Byte counts follow the key layout of the legacy near-sdk collections
+
+pub fn nested_prefix(prefix: &str, owner_id: &AccountId) -> Vec<u8> {
+    format!("{}{}", prefix, owner_id).into_bytes()
+}
+
+pub fn state_index_prefix(owner_id: &AccountId, state: TaskState) -> Vec<u8> {
+    format!("{}{}:{:?}", prefix::TASKS_BY_STATE, owner_id, state).into_bytes()
+}
+
+pub fn habit_history_prefix(habit_id: &str) -> Vec<u8> {
+    format!("{}{}", prefix::HABIT_HISTORY, habit_id).into_bytes()
+}
+
+pub fn borsh_len<T: BorshSerialize + ?Sized>(value: &T) -> u64 {
+    borsh::to_vec(value).map_or(0, |bytes| bytes.len() as u64)
+}
+
+pub fn record_bytes(key_bytes: u64, value_bytes: u64) -> u64 {
+    RECORD_OVERHEAD_BYTES + key_bytes + value_bytes
+}
+
+pub fn lookup_entry_bytes<K, V>(prefix: &str, key: &K, value: Option<&V>) -> u64
+where
+    K: BorshSerialize + ?Sized,
+    V: BorshSerialize,
+{
+    value.map_or(0, |value| record_bytes(prefix.len() as u64 + borsh_len(key), borsh_len(value)))
+}
+
+// An UnorderedMap entry, or an UnorderedSet one when there is no value: the
+// key's index record, the key in the keys vector and the value in the values vector
+pub fn unordered_entry_bytes(prefix_len: u64, key_bytes: u64, value_bytes: Option<u64>) -> u64 {
+    let vector_key_bytes = prefix_len + 1 + 8;
+    let index = record_bytes(prefix_len + 1 + key_bytes, 8);
+    let key = record_bytes(vector_key_bytes, key_bytes);
+    let value = value_bytes.map_or(0, |value_bytes| record_bytes(vector_key_bytes, value_bytes));
+    index + key + value
+}
+
+// One element of a Vector, keyed by its u64 index
+pub fn vector_entry_bytes(prefix_len: u64, value_bytes: u64) -> u64 {
+    record_bytes(prefix_len + 8, value_bytes)
+}
+
+// A TreeMap entry: the value record plus an AVL node holding the key, both
+// child links and the height
+pub fn tree_entry_bytes(prefix_len: u64, key_bytes: u64, value_bytes: u64) -> u64 {
+    let value = record_bytes(prefix_len + 1 + key_bytes, value_bytes);
+    let node = record_bytes(prefix_len + 1 + 8, 8 + key_bytes + 2 * 9 + 8);
+    value + node
+}
+
+// === Core Implementations ===
+impl EntityStorage {
+    pub fn new(count: u64, bytes: u64) -> Self {
+        Self {
+            count,
+            bytes,
+            cost: U128(bytes as u128 * env::storage_byte_cost().as_yoctonear()),
+        }
+    }
+}
+
+impl StorageAccount {
+    pub fn new(deposit: u128) -> Self {
+        Self {
+            deposit,
+            bytes_used: 0,
+        }
+    }
+
+    pub fn storage_cost(&self) -> u128 {
+        self.bytes_used as u128 * env::storage_byte_cost().as_yoctonear()
+    }
+
+    pub fn available(&self) -> u128 {
+        self.deposit.saturating_sub(self.storage_cost())
+    }
+
+    pub fn balance(&self) -> StorageBalance {
+        StorageBalance {
+            total: U128(self.deposit),
+            available: U128(self.available()),
+        }
+    }
+}
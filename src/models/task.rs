@@ -2,18 +2,22 @@ use schemars::JsonSchema;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
+    json_types::U128,
     env, AccountId};
 use crate::models::traits::{
-    Storable, StorageError, StorageMetrics,
+    Storable, StorageError,
     Ownable, OwnershipError};
 
-use crate::models::config::{task::*, time::*, storage::*};
+use crate::models::config::prefix;
+use crate::models::admin::ContractConfig;
+use crate::models::storage::{
+    borsh_len, state_index_prefix, tree_entry_bytes};
 
 pub type TaskId = String;
 
 // === Core State and Action Enums ===
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
-    Debug, PartialEq, Clone, Copy, JsonSchema)]
+    Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub enum Priority {
     Low,
@@ -85,6 +89,10 @@ pub enum TaskValidationError {
         reason: SubtaskError,
         current_count: usize,
     },
+    Dependencies {
+        reason: SubtaskError,
+        blocker_id: TaskId,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -130,13 +138,15 @@ pub enum TimingError {
 pub enum SubtaskError {
     DuplicateId,
     CircularDependency,
+    MaxDepthExceeded,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub enum TaskStateError {
     InvalidTransition { from: TaskState, to: TaskState },
-    InvalidActionForState { state: TaskState, action: TaskAction }
+    InvalidActionForState { state: TaskState, action: TaskAction },
+    Blocked { state: TaskState, blocker_ids: Vec<TaskId> }
 }
 
 // === Core Data Structures ===
@@ -158,12 +168,31 @@ pub struct Task {
     pub subtask_ids: Vec<TaskId>,
 }
 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskTreeNode {
+    pub task: Task,
+    pub completed_minutes: u32,
+    pub total_minutes: u32,
+    pub progress_percent: u8,
+    pub children: Vec<TaskTreeNode>,
+}
+
+// Outcome of one sweep_overdue batch
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OverdueSweep {
+    pub swept: Vec<TaskId>,
+    #[schemars(with = "String")]
+    pub keeper_fee: U128,
+}
+
 // === Trait Definitions ===
 pub trait TaskValidation {
-    fn validate_title(&mut self) -> Result<(), TaskValidationError>;
-    fn validate_description(&mut self) -> Result<(), TaskValidationError>;
-    fn validate_deadline(&self) -> Result<(), TaskValidationError>;
-    fn validate_estimated_time(&self) -> Result<(), TaskValidationError>;
+    fn validate_title(&mut self, config: &ContractConfig) -> Result<(), TaskValidationError>;
+    fn validate_description(&mut self, config: &ContractConfig) -> Result<(), TaskValidationError>;
+    fn validate_deadline(&self, config: &ContractConfig) -> Result<(), TaskValidationError>;
+    fn validate_estimated_time(&self, config: &ContractConfig) -> Result<(), TaskValidationError>;
     fn validate_timing(&self) -> Result<(), TaskValidationError>;
     fn validate_subtasks(&self) -> Result<(), TaskValidationError>;
     fn validate_state_for_action(&self, action: TaskAction) -> Result<(), TaskStateError>;
@@ -220,14 +249,23 @@ impl std::fmt::Display for TaskValidationError {
             },
             Self::Subtasks { reason, current_count } => {
                 write!(f, "Subtasks validation error: {:?} (count: {})", reason, current_count)
+            },
+            Self::Dependencies { reason, blocker_id } => {
+                write!(f, "Dependencies validation error: {:?} (blocker: {})", reason, blocker_id)
             }
         }
     }
 }
 
 // === Core Implementations ===
+impl TaskState {
+    pub const ALL: [TaskState; 4] = [TaskState::Created, TaskState::InProgress, TaskState::Completed, TaskState::Overdue];
+}
+
 impl Task {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
+        id: TaskId,
         title: String,
         description: String,
         priority: Priority,
@@ -235,9 +273,10 @@ impl Task {
         estimated_time: u32,
         time_slots: Vec<TaskTimeSlot>,
         owner_id: AccountId,
+        config: &ContractConfig,
     ) -> Result<Self, TaskError> {
         let mut task = Self {
-            id: format!("task-{}-{}", owner_id, env::block_timestamp()),
+            id,
             title,
             description,
             priority,
@@ -251,7 +290,7 @@ impl Task {
             subtask_ids: Vec::new(),
         };
 
-        task.validate()?;
+        task.validate(config)?;
         Ok(task)
     }
 
@@ -270,21 +309,21 @@ impl Task {
         }
     }
 
-    pub fn validate(&mut self) -> Result<(), TaskError> {
-        self.validate_title()
+    pub fn validate(&mut self, config: &ContractConfig) -> Result<(), TaskError> {
+        self.validate_title(config)
             .map_err(TaskError::Validation)?;
-        self.validate_description()
+        self.validate_description(config)
             .map_err(TaskError::Validation)?;
-        self.validate_deadline()
+        self.validate_deadline(config)
             .map_err(TaskError::Validation)?;
-        self.validate_estimated_time()
+        self.validate_estimated_time(config)
             .map_err(TaskError::Validation)?;
         self.validate_timing()
             .map_err(TaskError::Validation)?;
         self.validate_subtasks()
             .map_err(TaskError::Validation)?;
-        self.validate_storage()
-            .map_err(|e| TaskError::Storage(e))?;
+        self.validate_storage(config)
+            .map_err(TaskError::Storage)?;
         Ok(())
     }
 
@@ -323,6 +362,10 @@ impl Task {
         }
     }
 
+    pub fn remove_subtask(&mut self, subtask_id: &TaskId) {
+        self.subtask_ids.retain(|id| id != subtask_id);
+    }
+
     pub fn add_subtask(&mut self, subtask_id: TaskId) -> Result<(), TaskError> {
         if self.subtask_ids.contains(&subtask_id) {
             return Err(TaskError::Validation(TaskValidationError::Subtasks {
@@ -336,15 +379,36 @@ impl Task {
     }
 }
 
+impl TaskTreeNode {
+    pub fn new(task: Task, children: Vec<TaskTreeNode>) -> Self {
+        let own_completed = if task.state == TaskState::Completed { task.estimated_time } else { 0 };
+        let completed_minutes = own_completed + children.iter().map(|c| c.completed_minutes).sum::<u32>();
+        let total_minutes = task.estimated_time + children.iter().map(|c| c.total_minutes).sum::<u32>();
+        let progress_percent = if total_minutes == 0 {
+            0
+        } else {
+            (completed_minutes as u64 * 100 / total_minutes as u64) as u8
+        };
+
+        Self {
+            task,
+            completed_minutes,
+            total_minutes,
+            progress_percent,
+            children,
+        }
+    }
+}
+
 impl TaskValidation for Task {
-    fn validate_title(&mut self) -> Result<(), TaskValidationError> {
+    fn validate_title(&mut self, config: &ContractConfig) -> Result<(), TaskValidationError> {
         if self.title.is_empty() {
             return Err(TaskValidationError::Title {
                 reason: TitleError::Empty,
                 current_length: 0,
             });
         }
-        if self.title.len() > MAX_TITLE_LENGTH {
+        if self.title.len() > config.task_max_title_length as usize {
             return Err(TaskValidationError::Title {
                 reason: TitleError::TooLong,
                 current_length: self.title.len(),
@@ -354,8 +418,8 @@ impl TaskValidation for Task {
             let code = c as u32;
             // Allow tab (0x09), line feed (0x0A), and carriage return (0x0D)
             // Prohibit other control characters
-            (code <= 0x08) || (code >= 0x0B && code <= 0x0C) || 
-            (code >= 0x0E && code <= 0x1F) || (code == 0x7F)
+            (code <= 0x08) || (0x0B..=0x0C).contains(&code) || 
+            (0x0E..=0x1F).contains(&code) || (code == 0x7F)
         }) {
             return Err(TaskValidationError::Title {
                 reason: TitleError::InvalidCharacters,
@@ -366,8 +430,8 @@ impl TaskValidation for Task {
         Ok(())
     }
 
-    fn validate_description(&mut self) -> Result<(), TaskValidationError> {
-        if self.description.len() > MAX_DESCRIPTION_LENGTH {
+    fn validate_description(&mut self, config: &ContractConfig) -> Result<(), TaskValidationError> {
+        if self.description.len() > config.task_max_description_length as usize {
             return Err(TaskValidationError::Description {
                 reason: DescriptionError::TooLong,
                 current_length: self.description.len(),
@@ -377,8 +441,8 @@ impl TaskValidation for Task {
             let code = c as u32;
             // Allow tab (0x09), line feed (0x0A), and carriage return (0x0D)
             // Prohibit other control characters
-            (code <= 0x08) || (code >= 0x0B && code <= 0x0C) || 
-            (code >= 0x0E && code <= 0x1F) || (code == 0x7F)
+            (code <= 0x08) || (0x0B..=0x0C).contains(&code) || 
+            (0x0E..=0x1F).contains(&code) || (code == 0x7F)
         }) {
             return Err(TaskValidationError::Description {
                 reason: DescriptionError::InvalidCharacters,
@@ -389,7 +453,7 @@ impl TaskValidation for Task {
         Ok(())
     }
 
-    fn validate_deadline(&self) -> Result<(), TaskValidationError> {
+    fn validate_deadline(&self, config: &ContractConfig) -> Result<(), TaskValidationError> {
         if self.state == TaskState::Overdue {
             return Ok(());
         }
@@ -403,7 +467,7 @@ impl TaskValidation for Task {
             });
         }
     
-        if self.deadline >= current_time + MAX_FUTURE_TIME {
+        if self.deadline >= current_time.saturating_add(config.max_future_time) {
             return Err(TaskValidationError::Deadline {
                 reason: DeadlineError::TooFarInFuture,
                 provided_time: self.deadline,
@@ -427,18 +491,18 @@ impl TaskValidation for Task {
         Ok(())
     }
 
-    fn validate_estimated_time(&self) -> Result<(), TaskValidationError> {
-        if self.estimated_time >= MAX_MINUTES {
-            return Err(TaskValidationError::EstimatedTime {
+    fn validate_estimated_time(&self, config: &ContractConfig) -> Result<(), TaskValidationError> {
+        if self.estimated_time >= config.max_estimated_minutes {
+            Err(TaskValidationError::EstimatedTime {
                 reason: EstimatedTimeError::TooLong,
                 provided_estimated_time: self.estimated_time,
-            });
+            })
         }
         else if self.estimated_time == 0 {
-            return Err(TaskValidationError::EstimatedTime {
+            Err(TaskValidationError::EstimatedTime {
                 reason: EstimatedTimeError::Zero,
                 provided_estimated_time: self.estimated_time,
-            });
+            })
         }
         else {
             Ok(())
@@ -495,7 +559,7 @@ impl TaskValidation for Task {
             (TaskState::Completed, TaskAction::Update) => {
                 Err(TaskStateError::InvalidActionForState {
                     state: self.state,
-                    action: action,
+                    action,
                 })
             },
             _ => Ok(())
@@ -510,29 +574,19 @@ impl Ownable for Task {
 }
 
 impl Storable for Task {
-    const BASE_STORAGE: u64 = TASK_BASE_STORAGE;
-    const MAX_STORAGE: u64 = TASK_MAX_STORAGE;
-    
-    fn calculate_storage_metrics(&self) -> StorageMetrics {
-        
-        let dynamic_size = 
-            self.id.len() as u64 +
-            self.title.len() as u64 +
-            self.description.len() as u64 +
-            self.owner_id.to_string().len() as u64 +
-            self.parent_task_id.as_ref().map_or(0, |id| id.len() as u64) +
-            self.subtask_ids.iter().map(|id| id.len() as u64).sum::<u64>();
-            
-        let total_bytes = Self::BASE_STORAGE + dynamic_size;
-        let cost_per_byte = env::storage_byte_cost().as_yoctonear();
-
-        let metrics = StorageMetrics {
-            base_size: Self::BASE_STORAGE,
-            dynamic_size,
-            total_bytes,
-            cost_per_byte,
-            total_cost: cost_per_byte * total_bytes as u128,
-        };
-        metrics
+    const MAP_PREFIX: &'static str = prefix::TASKS;
+    const OWNER_INDEX_PREFIX: &'static str = prefix::TASKS_PER_OWNER;
+
+    fn storage_key(&self) -> &str {
+        &self.id
+    }
+
+    fn max_storage(config: &ContractConfig) -> u64 {
+        config.task_max_storage
+    }
+
+    fn index_bytes(&self) -> u64 {
+        let state_prefix_len = state_index_prefix(&self.owner_id, self.state).len() as u64;
+        tree_entry_bytes(state_prefix_len, borsh_len(&(self.deadline, &self.id)), 0)
     }
 }
@@ -1,14 +1,16 @@
 use schemars::JsonSchema;
-use std::collections::HashSet;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
     serde::{Deserialize, Serialize},
     env, AccountId};
 use crate::models::traits::{
-    Storable, StorageError, StorageMetrics,
+    Storable, StorageError,
     Ownable, OwnershipError};
 
-use crate::models::config::storage::*;
+use crate::models::config::{time::*, prefix};
+use crate::models::admin::ContractConfig;
+use crate::models::calendar::{day_index, day_start};
+use crate::models::recurrence::{RecurrencePattern, RecurrencePatternV0, RecurrenceError};
 
 pub type TimeSlotId = String;
 
@@ -29,7 +31,7 @@ pub enum TimeSlotValidationError {
         start_minutes: u32,
         end_minutes: u32,
     },
-    Recurrence(TimeSlotRecurrenceError)
+    Recurrence(RecurrenceError)
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -38,13 +40,6 @@ pub enum TimeSlotTimingError {
     InvalidTimeOfDay,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(crate = "near_sdk::serde")]
-pub enum TimeSlotRecurrenceError {
-    EmptyDays,
-    InvalidPattern
-}
-
 // === Core Data Structures ===
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
     Clone, JsonSchema)]
@@ -63,10 +58,11 @@ pub struct TimeSlot {
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
     Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
-pub struct RecurrencePattern {
-    pub frequency: Frequency,
-    pub interval: Option<u32>,
-    pub specific_days: Option<Vec<DayOfWeek>>,
+pub struct SlotOccurrence {
+    pub slot_id: TimeSlotId,
+    pub slot_type: SlotType,
+    pub start_time: u64,
+    pub end_time: u64,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
@@ -77,14 +73,6 @@ pub enum SlotType {
     WorkingHours
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
-    PartialEq, Debug, Clone, JsonSchema)]
-#[serde(crate = "near_sdk::serde")]
-pub enum Frequency {
-    Daily,
-    Custom,
-}
-
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, 
     Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
@@ -98,6 +86,32 @@ pub enum DayOfWeek {
     Sunday,
 }
 
+// Layout before monthly and yearly recurrence rules
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TimeSlotV0 {
+    id: TimeSlotId,
+    start_minutes: u32,
+    end_minutes: u32,
+    duration: Option<u32>,
+    recurrence: RecurrencePatternV0,
+    owner_id: AccountId,
+    slot_type: SlotType,
+}
+
+impl From<TimeSlotV0> for TimeSlot {
+    fn from(old: TimeSlotV0) -> Self {
+        Self {
+            id: old.id,
+            start_minutes: old.start_minutes,
+            end_minutes: old.end_minutes,
+            duration: old.duration,
+            recurrence: old.recurrence.into(),
+            owner_id: old.owner_id,
+            slot_type: old.slot_type,
+        }
+    }
+}
+
 // === Trait Definitions ===
 pub trait TimeSlotValidation {
     fn validate_recurrence(&self) -> Result<(), TimeSlotValidationError>;
@@ -145,7 +159,7 @@ impl std::fmt::Display for TimeSlotValidationError {
                 write!(f, "Timing error: {:?} (start: {}, end: {})", reason, start_minutes, end_minutes)
             },
             Self::Recurrence(reason) => {
-                write!(f, "Recurrence error: {:?}", reason)
+                write!(f, "Recurrence error: {}", reason)
             }
         }
     }
@@ -154,10 +168,12 @@ impl std::fmt::Display for TimeSlotValidationError {
 // === Core Implementations ===
 impl TimeSlot {    
     pub fn new(
+        id: TimeSlotId,
         start_minutes: u32,
         end_minutes: u32,
         recurrence: RecurrencePattern,
         owner_id: AccountId,
+        config: &ContractConfig,
     ) -> Result<Self, TimeSlotError> {
         if start_minutes >= 1440 || end_minutes >= 1440 || 
             start_minutes == end_minutes {
@@ -176,50 +192,87 @@ impl TimeSlot {
         };
 
         let mut time_slot = Self {
-            id: format!("time_slot-{}-{}", owner_id, env::block_timestamp()),
+            id,
             start_minutes,
             end_minutes,
             duration: Some(duration),
-            recurrence,
+            recurrence: recurrence.anchored(env::block_timestamp()),
             owner_id,
             slot_type: SlotType::WorkingHours,
         };
         
-        time_slot.validate()?;
+        time_slot.validate(config)?;
         Ok(time_slot)
     }
 
-    pub fn validate(&mut self) -> Result<(), TimeSlotError> {
+    pub fn validate(&mut self, config: &ContractConfig) -> Result<(), TimeSlotError> {
         self.validate_recurrence()
             .map_err(TimeSlotError::Validation)?;
-        self.validate_storage()
-            .map_err(|e| TimeSlotError::Storage(e))?;
+        self.validate_storage(config)
+            .map_err(TimeSlotError::Storage)?;
         Ok(())
     }
 
-    pub fn overlaps_with(&self, other: &TimeSlot) -> bool {
-        // If slots are of different types (one is Break and one is WorkingHours),
-        // they are allowed to overlap
-        if self.slot_type != other.slot_type {
-            return false;
-        }
-        
-        if self.start_minutes < self.end_minutes && other.start_minutes < other.end_minutes {
-            // Normal case
-            return self.start_minutes < other.end_minutes && self.end_minutes > other.start_minutes;
+    // Whether the slot's time of day meets [start_minutes, end_minutes), which may
+    // also wrap past midnight
+    pub fn covers_minutes(&self, start_minutes: u32, end_minutes: u32) -> bool {
+        let ranges = minute_ranges(start_minutes, end_minutes);
+        minute_ranges(self.start_minutes, self.end_minutes)
+            .iter()
+            .any(|own| ranges.iter().any(|range| own.0 < range.1 && range.0 < own.1))
+    }
 
-        } else if self.start_minutes >= self.end_minutes && other.start_minutes < other.end_minutes {
-            // Self wraps around midnight, other doesn't
-            return self.start_minutes < other.end_minutes || self.end_minutes > other.start_minutes;
+    pub fn occurs_on_day(&self, day: u64, utc_offset_minutes: i32) -> bool {
+        self.recurrence.occurs_on_day(day, utc_offset_minutes)
+    }
 
-        } else if self.start_minutes < self.end_minutes && other.start_minutes >= other.end_minutes {
-            // Other wraps around midnight, self doesn't
-            return other.start_minutes < self.end_minutes || other.end_minutes > self.start_minutes;
+    // Slot minutes are local time for the owner's UTC offset
+    pub fn occurrences_between(
+        &self,
+        start_time: u64,
+        end_time: u64,
+        utc_offset_minutes: i32
+    ) -> Vec<SlotOccurrence> {
+        let mut occurrences = Vec::new();
+        if end_time <= start_time {
+            return occurrences;
+        }
 
-        } else {
-            // Both wrap around midnight
-            return true;
+        // Start a day early so an occurrence wrapping past midnight into the range is included
+        let first_day = day_index(start_time, utc_offset_minutes).saturating_sub(1);
+        let last_day = day_index(end_time - 1, utc_offset_minutes);
+
+        for day in self.recurrence.occurrence_days(first_day, last_day, utc_offset_minutes) {
+            let midnight = day_start(day, utc_offset_minutes);
+            let occurrence_start = midnight + self.start_minutes as u64 * NANOS_PER_MINUTE;
+            let occurrence_end = if self.end_minutes > self.start_minutes {
+                midnight + self.end_minutes as u64 * NANOS_PER_MINUTE
+            } else {
+                // Wraparound case
+                midnight + NANOS_PER_DAY + self.end_minutes as u64 * NANOS_PER_MINUTE
+            };
+
+            if occurrence_start < end_time && occurrence_end > start_time {
+                occurrences.push(SlotOccurrence {
+                    slot_id: self.id.clone(),
+                    slot_type: self.slot_type,
+                    start_time: occurrence_start,
+                    end_time: occurrence_end,
+                });
+            }
         }
+        occurrences
+    }
+}
+
+// Splits a time of day range at midnight when it wraps
+fn minute_ranges(start_minutes: u32, end_minutes: u32) -> Vec<(u32, u32)> {
+    if start_minutes < end_minutes {
+        vec![(start_minutes, end_minutes)]
+    } else if start_minutes > end_minutes {
+        vec![(start_minutes, MAX_MINUTES), (0, end_minutes)]
+    } else {
+        Vec::new()
     }
 }
 
@@ -230,84 +283,20 @@ impl Ownable for TimeSlot {
 }
 
 impl Storable for TimeSlot {
-    const BASE_STORAGE: u64 = TIME_SLOT_BASE_STORAGE;
-    const MAX_STORAGE: u64 = TIME_SLOT_MAX_STORAGE;
+    const MAP_PREFIX: &'static str = prefix::TIME_SLOTS;
+    const OWNER_INDEX_PREFIX: &'static str = prefix::TIME_SLOTS_PER_OWNER;
 
-    fn calculate_storage_metrics(&self) -> StorageMetrics {
-        
-        let dynamic_size = 
-            self.id.len() as u64 +
-            self.owner_id.to_string().len() as u64 +
-            match &self.recurrence {
-                RecurrencePattern { specific_days: Some(days), .. } => {
-                    days.len() as u64 * std::mem::size_of::<DayOfWeek>() as u64
-                },
-                _ => 0,
-            };
-            
-        let total_bytes = Self::BASE_STORAGE + dynamic_size;
-        let cost_per_byte = env::storage_byte_cost().as_yoctonear();
-        let metrics = StorageMetrics {
-            base_size: Self::BASE_STORAGE,
-            dynamic_size,
-            total_bytes,
-            cost_per_byte,
-            total_cost: cost_per_byte * total_bytes as u128,
-        };
-        metrics
+    fn storage_key(&self) -> &str {
+        &self.id
     }
-}
 
-impl TimeSlotValidation for TimeSlot {
-    fn validate_recurrence(&self) -> Result<(), TimeSlotValidationError> {
-        match &self.recurrence {
-            RecurrencePattern { frequency: Frequency::Custom, specific_days: None, .. } => {
-                return Err(TimeSlotValidationError::Recurrence(TimeSlotRecurrenceError::EmptyDays));
-            },
-            _ => {}
-        }
-        Ok(())
+    fn max_storage(config: &ContractConfig) -> u64 {
+        config.time_slot_max_storage
     }
 }
 
-impl RecurrencePattern {
-    pub fn new_daily() -> Self {
-        Self {
-            frequency: Frequency::Daily,
-            interval: Some(1),
-            specific_days: None,
-        }
-    }
-
-    pub fn new_custom(days: Vec<DayOfWeek>) -> Self {
-        if days.is_empty() {
-            env::panic_str("Must specify at least one day");
-        }
-
-        let mut unique_days: Vec<DayOfWeek> = days.into_iter().collect::<HashSet<_>>().into_iter().collect();
-        unique_days.sort();
-
-        RecurrencePattern {
-            frequency: Frequency::Custom,
-            interval: None,
-            specific_days: Some(unique_days),
-        }
-    }
-
-    pub fn is_valid(&self) -> bool {
-        match self.frequency {
-            Frequency::Custom => {
-                // Custom frequency must have specific days and no interval
-                self.specific_days.is_some() && 
-                !self.specific_days.as_ref().unwrap().is_empty() && 
-                self.interval.is_none()
-            }
-            Frequency::Daily => {
-                // Daily frequency must have an interval > 0 and no specific days
-                self.interval.is_some() && 
-                self.interval.unwrap() > 0 && 
-                self.specific_days.is_none()
-            }
-        }
+impl TimeSlotValidation for TimeSlot {
+    fn validate_recurrence(&self) -> Result<(), TimeSlotValidationError> {
+        self.recurrence.validate().map_err(TimeSlotValidationError::Recurrence)
     }
 }
\ No newline at end of file
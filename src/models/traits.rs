@@ -3,12 +3,20 @@ use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 
+use crate::models::storage::{borsh_len, nested_prefix, unordered_entry_bytes};
+use crate::models::versioned::VERSION_HEADER_BYTES;
+use crate::models::admin::ContractConfig;
+
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, JsonSchema, 
     Clone, PartialEq, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub enum StorageError {
     InsufficientBalance { required: u128, available: u128 },
-    ExceedsMaxSize { size: u64, max_allowed: u64 }
+    ExceedsMaxSize { size: u64, max_allowed: u64 },
+    NotRegistered {
+        #[schemars(with = "String")]
+        account_id: AccountId
+    }
 }
 
 impl std::fmt::Display for StorageError {
@@ -19,6 +27,9 @@ impl std::fmt::Display for StorageError {
             },
             Self::ExceedsMaxSize { size, max_allowed } => {
                 write!(f, "Exceeds max size: size {}, max allowed {}", size, max_allowed)
+            },
+            Self::NotRegistered { account_id } => {
+                write!(f, "Account {} is not registered for storage", account_id)
             }
         }
     }
@@ -61,30 +72,56 @@ pub trait Ownable {
     }
 }
 
-pub trait Storable {
-    const BASE_STORAGE: u64;
-    const MAX_STORAGE: u64;
+pub trait Storable: Ownable + BorshSerialize {
+    // Prefixes of the contract map holding the entity and of its per-owner id set
+    const MAP_PREFIX: &'static str;
+    const OWNER_INDEX_PREFIX: &'static str;
+
+    fn storage_key(&self) -> &str;
+
+    fn max_storage(config: &ContractConfig) -> u64;
+
+    // Records in secondary indices beyond the per-owner set
+    fn index_bytes(&self) -> u64 {
+        0
+    }
+
+    // base_size covers the entity's own map entry, dynamic_size the index entries
+    fn calculate_storage_metrics(&self) -> StorageMetrics {
+        let key_bytes = borsh_len(self.storage_key());
+        let base_size = unordered_entry_bytes(
+            Self::MAP_PREFIX.len() as u64,
+            key_bytes,
+            Some(VERSION_HEADER_BYTES + borsh_len(self)),
+        );
 
-    fn calculate_storage_metrics(&self) -> StorageMetrics;
+        let owner_prefix_len = nested_prefix(Self::OWNER_INDEX_PREFIX, self.get_owner_id()).len() as u64;
+        let dynamic_size = unordered_entry_bytes(owner_prefix_len, key_bytes, None) + self.index_bytes();
+
+        let total_bytes = base_size + dynamic_size;
+        let cost_per_byte = env::storage_byte_cost().as_yoctonear();
+        StorageMetrics {
+            base_size,
+            dynamic_size,
+            total_bytes,
+            cost_per_byte,
+            total_cost: cost_per_byte * total_bytes as u128,
+        }
+    }
     
-    fn validate_storage(&mut self) -> Result<(), StorageError> {
+    // Only bounds the entity size; paying for the bytes is enforced per account
+    // through the contract's NEP-145 storage deposits
+    fn validate_storage(&mut self, config: &ContractConfig) -> Result<(), StorageError> {
         let metrics = self.calculate_storage_metrics();
+        let max_allowed = Self::max_storage(config);
         
-        if metrics.total_bytes > Self::MAX_STORAGE {
+        if metrics.total_bytes > max_allowed {
             return Err(StorageError::ExceedsMaxSize {
                 size: metrics.total_bytes,
-                max_allowed: Self::MAX_STORAGE,
-            });
-        }
-        
-        let available = env::account_balance().as_yoctonear();
-        if available < metrics.total_cost {
-            return Err(StorageError::InsufficientBalance {
-                required: metrics.total_cost,
-                available,
+                max_allowed,
             });
         }
-    
+
         Ok(())
     }
 }
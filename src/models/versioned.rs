@@ -0,0 +1,360 @@
+use std::io::{self, Read, Write};
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::models::task::Task;
+use crate::models::habit::{Habit, HabitV0};
+use crate::models::reward::Reward;
+use crate::models::time_slot::{TimeSlot, TimeSlotV0};
+
+// Records written before versioning start with the u32 length of their
+// generated id, whose low byte never reaches this tag
+pub const VERSION_TAG: u8 = 0xFF;
+pub const VERSION_HEADER_BYTES: u64 = 2;
+
+// === Version Header ===
+enum StoredVersion {
+    Tagged(u8),
+    // Untagged record, with the byte already read from it
+    Legacy(u8),
+}
+
+fn read_version<R: Read>(reader: &mut R) -> io::Result<StoredVersion> {
+    let first = u8::deserialize_reader(reader)?;
+    if first == VERSION_TAG {
+        Ok(StoredVersion::Tagged(u8::deserialize_reader(reader)?))
+    } else {
+        Ok(StoredVersion::Legacy(first))
+    }
+}
+
+fn write_version<W: Write>(writer: &mut W, version: u8) -> io::Result<()> {
+    writer.write_all(&[VERSION_TAG, version])
+}
+
+fn unknown_version(entity: &str, version: u8) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Unknown {} version: {}", entity, version),
+    )
+}
+
+// === Versioned Entities ===
+// V0 is the layout deployed before versioning; add a variant per layout change
+// and convert older variants in the From implementations
+pub enum VersionedTask {
+    V0(Task),
+}
+
+pub enum VersionedHabit {
+    V0(HabitV0),
+    V1(Habit),
+}
+
+pub enum VersionedReward {
+    V0(Reward),
+}
+
+pub enum VersionedTimeSlot {
+    V0(TimeSlotV0),
+    V1(TimeSlot),
+}
+
+// === Borsh Implementations ===
+impl BorshSerialize for VersionedTask {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::V0(task) => {
+                write_version(writer, 0)?;
+                task.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedTask {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match read_version(reader)? {
+            StoredVersion::Tagged(0) => Ok(Self::V0(Task::deserialize_reader(reader)?)),
+            StoredVersion::Legacy(first) => {
+                Ok(Self::V0(Task::deserialize_reader(&mut [first].as_slice().chain(reader))?))
+            },
+            StoredVersion::Tagged(version) => Err(unknown_version("Task", version)),
+        }
+    }
+}
+
+impl BorshSerialize for VersionedHabit {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::V0(habit) => {
+                write_version(writer, 0)?;
+                habit.serialize(writer)
+            },
+            Self::V1(habit) => {
+                write_version(writer, 1)?;
+                habit.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedHabit {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match read_version(reader)? {
+            StoredVersion::Tagged(0) => Ok(Self::V0(HabitV0::deserialize_reader(reader)?)),
+            StoredVersion::Tagged(1) => Ok(Self::V1(Habit::deserialize_reader(reader)?)),
+            StoredVersion::Legacy(first) => {
+                Ok(Self::V0(HabitV0::deserialize_reader(&mut [first].as_slice().chain(reader))?))
+            },
+            StoredVersion::Tagged(version) => Err(unknown_version("Habit", version)),
+        }
+    }
+}
+
+impl BorshSerialize for VersionedReward {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::V0(reward) => {
+                write_version(writer, 0)?;
+                reward.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedReward {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match read_version(reader)? {
+            StoredVersion::Tagged(0) => Ok(Self::V0(Reward::deserialize_reader(reader)?)),
+            StoredVersion::Legacy(first) => {
+                Ok(Self::V0(Reward::deserialize_reader(&mut [first].as_slice().chain(reader))?))
+            },
+            StoredVersion::Tagged(version) => Err(unknown_version("Reward", version)),
+        }
+    }
+}
+
+impl BorshSerialize for VersionedTimeSlot {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            Self::V0(time_slot) => {
+                write_version(writer, 0)?;
+                time_slot.serialize(writer)
+            },
+            Self::V1(time_slot) => {
+                write_version(writer, 1)?;
+                time_slot.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for VersionedTimeSlot {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match read_version(reader)? {
+            StoredVersion::Tagged(0) => Ok(Self::V0(TimeSlotV0::deserialize_reader(reader)?)),
+            StoredVersion::Tagged(1) => Ok(Self::V1(TimeSlot::deserialize_reader(reader)?)),
+            StoredVersion::Legacy(first) => {
+                Ok(Self::V0(TimeSlotV0::deserialize_reader(&mut [first].as_slice().chain(reader))?))
+            },
+            StoredVersion::Tagged(version) => Err(unknown_version("TimeSlot", version)),
+        }
+    }
+}
+
+// === Conversions ===
+impl From<VersionedTask> for Task {
+    fn from(versioned: VersionedTask) -> Self {
+        match versioned {
+            VersionedTask::V0(task) => task,
+        }
+    }
+}
+
+impl From<Task> for VersionedTask {
+    fn from(task: Task) -> Self {
+        Self::V0(task)
+    }
+}
+
+impl From<VersionedHabit> for Habit {
+    fn from(versioned: VersionedHabit) -> Self {
+        match versioned {
+            VersionedHabit::V0(habit) => habit.into(),
+            VersionedHabit::V1(habit) => habit,
+        }
+    }
+}
+
+impl From<Habit> for VersionedHabit {
+    fn from(habit: Habit) -> Self {
+        Self::V1(habit)
+    }
+}
+
+impl From<VersionedReward> for Reward {
+    fn from(versioned: VersionedReward) -> Self {
+        match versioned {
+            VersionedReward::V0(reward) => reward,
+        }
+    }
+}
+
+impl From<Reward> for VersionedReward {
+    fn from(reward: Reward) -> Self {
+        Self::V0(reward)
+    }
+}
+
+impl From<VersionedTimeSlot> for TimeSlot {
+    fn from(versioned: VersionedTimeSlot) -> Self {
+        match versioned {
+            VersionedTimeSlot::V0(time_slot) => time_slot.into(),
+            VersionedTimeSlot::V1(time_slot) => time_slot,
+        }
+    }
+}
+
+impl From<TimeSlot> for VersionedTimeSlot {
+    fn from(time_slot: TimeSlot) -> Self {
+        Self::V1(time_slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::{borsh, AccountId};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+    use crate::models::{
+        Priority, Frequency, DayOfWeek, SlotType, ContractConfig, Ownable,
+    };
+
+    const NOW: u64 = 1_700_000_000_000_000_000;
+    const DAY: u64 = 86_400_000_000_000;
+
+    fn setup() -> AccountId {
+        testing_env!(VMContextBuilder::new().block_timestamp(NOW).build());
+        "alice.near".parse().unwrap()
+    }
+
+    fn task(id: String, owner_id: AccountId) -> Task {
+        Task::new(
+            id, "Read".to_string(), String::new(), Priority::Medium, NOW + DAY, 30, Vec::new(),
+            owner_id, &ContractConfig::default(),
+        ).unwrap_or_else(|_| panic!("invalid task"))
+    }
+
+    fn reward(id: String, owner_id: AccountId) -> Reward {
+        Reward::new(id, "Coffee".to_string(), String::new(), 10, owner_id, &ContractConfig::default())
+            .unwrap_or_else(|_| panic!("invalid reward"))
+    }
+
+    // Untagged habit and time slot records, field by field in the baseline order
+    fn legacy_habit(id: String, owner_id: AccountId) -> Vec<u8> {
+        let recurrence = (Frequency::Custom, None::<u32>, Some(vec![DayOfWeek::Friday]));
+        borsh::to_vec(&(id, "task-alice.near-1".to_string(), recurrence, 7u32, NOW - DAY, owner_id)).unwrap()
+    }
+
+    fn legacy_time_slot(id: String, owner_id: AccountId) -> Vec<u8> {
+        let recurrence = (Frequency::Daily, Some(2u32), None::<Vec<DayOfWeek>>);
+        borsh::to_vec(&(id, 60u32, 120u32, Some(60u32), recurrence, owner_id, SlotType::Break)).unwrap()
+    }
+
+    #[test]
+    fn untagged_records_read_as_the_baseline_layout() {
+        let alice = setup();
+
+        let task = task("task-alice.near-1".to_string(), alice.clone());
+        let legacy = borsh::to_vec(&task).unwrap();
+        let versioned = VersionedTask::try_from_slice(&legacy).unwrap();
+        assert!(matches!(versioned, VersionedTask::V0(_)));
+        assert_eq!(borsh::to_vec(&Task::from(versioned)).unwrap(), legacy);
+
+        let reward = reward("reward-alice.near-1".to_string(), alice.clone());
+        let legacy = borsh::to_vec(&reward).unwrap();
+        let versioned = VersionedReward::try_from_slice(&legacy).unwrap();
+        assert!(matches!(versioned, VersionedReward::V0(_)));
+        assert_eq!(borsh::to_vec(&Reward::from(versioned)).unwrap(), legacy);
+
+        let versioned = VersionedHabit::try_from_slice(&legacy_habit("habit-alice.near-1".to_string(), alice.clone()))
+            .unwrap();
+        assert!(matches!(versioned, VersionedHabit::V0(_)));
+        let habit = Habit::from(versioned);
+        assert_eq!(habit.id, "habit-alice.near-1");
+        assert_eq!(habit.task_id, "task-alice.near-1");
+        assert_eq!(habit.recurrence.frequency, Frequency::Custom);
+        assert_eq!(habit.recurrence.interval, None);
+        assert_eq!(habit.recurrence.specific_days, Some(vec![DayOfWeek::Friday]));
+        assert_eq!(habit.streak, 7);
+        assert_eq!(habit.last_completed, NOW - DAY);
+        assert_eq!(habit.get_owner_id(), &alice);
+
+        let versioned = VersionedTimeSlot::try_from_slice(&legacy_time_slot("time_slot-alice.near-1".to_string(), alice.clone()))
+            .unwrap();
+        assert!(matches!(versioned, VersionedTimeSlot::V0(_)));
+        let time_slot = TimeSlot::from(versioned);
+        assert_eq!(time_slot.id, "time_slot-alice.near-1");
+        assert_eq!(time_slot.start_minutes, 60);
+        assert_eq!(time_slot.end_minutes, 120);
+        assert_eq!(time_slot.duration, Some(60));
+        assert_eq!(time_slot.recurrence.frequency, Frequency::Daily);
+        assert_eq!(time_slot.recurrence.interval, Some(2));
+        assert_eq!(time_slot.get_owner_id(), &alice);
+        assert_eq!(time_slot.slot_type, SlotType::Break);
+    }
+
+    #[test]
+    fn current_records_are_tagged() {
+        let alice = setup();
+        let task = task("task-alice.near-1".to_string(), alice.clone());
+        let reward = reward("reward-alice.near-1".to_string(), alice.clone());
+        let habit = Habit::from(VersionedHabit::try_from_slice(&legacy_habit("habit-alice.near-1".to_string(), alice.clone())).unwrap());
+        let time_slot = TimeSlot::from(VersionedTimeSlot::try_from_slice(&legacy_time_slot("time_slot-alice.near-1".to_string(), alice)).unwrap());
+
+        let bytes = borsh::to_vec(&VersionedTask::from(task)).unwrap();
+        assert_eq!(bytes[..2], [VERSION_TAG, 0]);
+        assert!(matches!(VersionedTask::try_from_slice(&bytes).unwrap(), VersionedTask::V0(_)));
+
+        let bytes = borsh::to_vec(&VersionedReward::from(reward)).unwrap();
+        assert_eq!(bytes[..2], [VERSION_TAG, 0]);
+        assert!(matches!(VersionedReward::try_from_slice(&bytes).unwrap(), VersionedReward::V0(_)));
+
+        let bytes = borsh::to_vec(&VersionedHabit::from(habit)).unwrap();
+        assert_eq!(bytes[..2], [VERSION_TAG, 1]);
+        assert!(matches!(VersionedHabit::try_from_slice(&bytes).unwrap(), VersionedHabit::V1(_)));
+
+        let bytes = borsh::to_vec(&VersionedTimeSlot::from(time_slot)).unwrap();
+        assert_eq!(bytes[..2], [VERSION_TAG, 1]);
+        assert!(matches!(VersionedTimeSlot::try_from_slice(&bytes).unwrap(), VersionedTimeSlot::V1(_)));
+    }
+
+    // An untagged record starts with the low byte of its id length. Ids are
+    // generated from the owner's account id, at most 64 bytes long, so even the
+    // longest one stays below the tag
+    #[test]
+    fn untagged_records_never_start_with_the_tag() {
+        setup();
+        let owner_id: AccountId = "a".repeat(64).parse().unwrap();
+
+        for entity in ["task", "habit", "reward", "time_slot"] {
+            let ids = [
+                // Before the nonce was added
+                format!("{}-{}-{}", entity, owner_id, u64::MAX),
+                format!("{}-{}-{}-{}", entity, owner_id, u64::MAX, u64::MAX),
+            ];
+            for id in ids {
+                assert!(id.len() < VERSION_TAG as usize);
+                let record = match entity {
+                    "task" => borsh::to_vec(&task(id.clone(), owner_id.clone())).unwrap(),
+                    "habit" => legacy_habit(id.clone(), owner_id.clone()),
+                    "reward" => borsh::to_vec(&reward(id.clone(), owner_id.clone())).unwrap(),
+                    _ => legacy_time_slot(id.clone(), owner_id.clone()),
+                };
+                assert_eq!(record[0], id.len() as u8);
+                assert_ne!(record[0], VERSION_TAG);
+            }
+        }
+    }
+}